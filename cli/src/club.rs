@@ -0,0 +1,129 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+use clap::{Args, Subcommand};
+use degods_poker::state::*;
+use degods_poker_client::{accounts, fetch, instructions, pda};
+use serde_json::{json, Value};
+
+use crate::context::Context;
+use crate::output::sent;
+use crate::Result;
+
+#[derive(Subcommand)]
+pub enum ClubCommand {
+    /// Create a club owned by the signer, with defaults for its tables.
+    Create {
+        /// At most 32 bytes; the club's address is derived from it.
+        name: String,
+        #[command(flatten)]
+        settings: SettingsArgs,
+    },
+    /// Change the defaults new tables and tournaments in the club start with.
+    Update {
+        club: Pubkey,
+        #[command(flatten)]
+        settings: SettingsArgs,
+    },
+    /// Replace the club's admins, who can manage every table in it.
+    SetAdmins {
+        club: Pubkey,
+        /// Leave empty to remove every admin.
+        admins: Vec<Pubkey>,
+    },
+    /// Replace the players allowed at the club's tables; empty lets anyone in.
+    SetAllowlist { club: Pubkey, players: Vec<Pubkey> },
+}
+
+/// Settings left out keep their current value, or on a new club default to
+/// no rake, paid to the owner, in native SOL.
+#[derive(Args)]
+pub struct SettingsArgs {
+    #[arg(long)]
+    rake_bps: Option<u16>,
+    #[arg(long)]
+    rake_cap: Option<u64>,
+    #[arg(long)]
+    rake_recipient: Option<Pubkey>,
+    #[arg(long)]
+    mint: Option<Pubkey>,
+}
+
+impl SettingsArgs {
+    fn over(self, current: ClubSettingsData) -> ClubSettingsData {
+        ClubSettingsData {
+            default_rake_bps: self.rake_bps.unwrap_or(current.default_rake_bps),
+            default_rake_cap: self.rake_cap.unwrap_or(current.default_rake_cap),
+            default_rake_recipient: self
+                .rake_recipient
+                .unwrap_or(current.default_rake_recipient),
+            default_token_mint: self.mint.unwrap_or(current.default_token_mint),
+        }
+    }
+}
+
+pub async fn run(ctx: &Context, command: ClubCommand) -> Result<Value> {
+    let signer = ctx.pubkey();
+    Ok(match command {
+        ClubCommand::Create { name, settings } => {
+            let club = pda::club(&signer, &name);
+            let settings = settings.over(ClubSettingsData {
+                default_rake_bps: 0,
+                default_rake_cap: 0,
+                default_rake_recipient: signer,
+                default_token_mint: native_mint::ID,
+            });
+            let instruction = instructions::create_club(
+                accounts::CreateClubAccounts {
+                    club,
+                    owner: signer,
+                    system_program: system_program::ID,
+                },
+                name,
+                settings,
+            );
+            let signature = ctx.send(&[instruction]).await?;
+            json!({
+                "club": club.to_string(),
+                "signature": signature.to_string(),
+            })
+        }
+        ClubCommand::Update { club, settings } => {
+            let current = fetch::account::<Club>(&ctx.rpc, &club).await?;
+            let settings = settings.over(ClubSettingsData {
+                default_rake_bps: current.default_rake_bps,
+                default_rake_cap: current.default_rake_cap,
+                default_rake_recipient: current.default_rake_recipient,
+                default_token_mint: current.default_token_mint,
+            });
+            let instruction = instructions::update_club_settings(
+                accounts::ClubOwnerAccounts {
+                    club,
+                    owner: signer,
+                },
+                settings,
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        ClubCommand::SetAdmins { club, admins } => {
+            let instruction = instructions::set_club_admins(
+                accounts::ClubOwnerAccounts {
+                    club,
+                    owner: signer,
+                },
+                ClubMembersData { members: admins },
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        ClubCommand::SetAllowlist { club, players } => {
+            let instruction = instructions::set_club_allowlist(
+                accounts::ClubAdminAccounts {
+                    club,
+                    admin: signer,
+                },
+                ClubMembersData { members: players },
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+    })
+}
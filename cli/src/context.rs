@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use degods_poker::state::RoleAccount;
+use degods_poker::vault::is_native;
+use degods_poker_client::{fetch, pda, transactions, Error};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+use crate::Result;
+
+/// Where the Solana CLI keeps its default keypair.
+const DEFAULT_KEYPAIR: &str = ".config/solana/id.json";
+
+/// Most token accounts created ahead of a call in one transaction.
+pub const TOKEN_ACCOUNTS_PER_TRANSACTION: usize = 6;
+
+/// The RPC connection and the keypair that signs and pays for everything.
+pub struct Context {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Context {
+    pub fn new(rpc: RpcClient, keypair: Option<PathBuf>) -> Result<Self> {
+        let path = match keypair {
+            Some(path) => path,
+            None => PathBuf::from(std::env::var("HOME")?).join(DEFAULT_KEYPAIR),
+        };
+        let payer = read_keypair_file(&path)
+            .map_err(|e| format!("can't read keypair {}: {e}", path.display()))?;
+        Ok(Context { rpc, payer })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    pub async fn send(&self, instructions: &[Instruction]) -> Result<Signature> {
+        Ok(transactions::send(&self.rpc, &self.payer, instructions, &[]).await?)
+    }
+
+    /// Instructions creating whichever of the `wallets`' token accounts don't
+    /// exist yet, paid for by the signer. Native SOL needs none.
+    pub async fn create_token_accounts(
+        &self,
+        wallets: &[Pubkey],
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        if is_native(mint) || wallets.is_empty() {
+            return Ok(Vec::new());
+        }
+        let addresses: Vec<Pubkey> = wallets
+            .iter()
+            .map(|w| pda::payout_address(w, mint, token_program))
+            .collect();
+        let accounts = self
+            .rpc
+            .get_multiple_accounts(&addresses)
+            .await
+            .map_err(Error::from)?;
+        Ok(wallets
+            .iter()
+            .zip(accounts)
+            .filter(|(_, account)| account.is_none())
+            .map(|(wallet, _)| {
+                create_associated_token_account_idempotent(
+                    &self.pubkey(),
+                    wallet,
+                    mint,
+                    token_program,
+                )
+            })
+            .collect())
+    }
+
+    /// The signer's role on `scope`, or failing that on its club, when
+    /// `use_role` asks to act through one rather than as owner.
+    pub async fn role(
+        &self,
+        use_role: bool,
+        scope: &Pubkey,
+        club: &Pubkey,
+    ) -> Result<Option<Pubkey>> {
+        if !use_role {
+            return Ok(None);
+        }
+        let mut scopes = vec![*scope];
+        scopes.extend(club_of(club));
+        for scope in scopes {
+            let address = pda::role(&scope, &self.pubkey());
+            match fetch::account::<RoleAccount>(&self.rpc, &address).await {
+                Ok(_) => return Ok(Some(address)),
+                Err(Error::AccountNotFound(_)) => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Err(format!("{} holds no role on {scope}", self.pubkey()).into())
+    }
+}
+
+/// The club to pass alongside a game or tournament, if it was created under one.
+pub fn club_of(club: &Pubkey) -> Option<Pubkey> {
+    (*club != Pubkey::default()).then_some(*club)
+}
+
+/// `address` on token tables; native SOL tables leave token accounts and the
+/// mint out.
+pub fn token(mint: &Pubkey, address: Pubkey) -> Option<Pubkey> {
+    (!is_native(mint)).then_some(address)
+}
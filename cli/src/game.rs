@@ -0,0 +1,722 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+use clap::{Args, Subcommand, ValueEnum};
+use degods_poker::state::*;
+use degods_poker_client::transactions::{self, Ejection};
+use degods_poker_client::{accounts, fetch, instructions, pda, Error, ID};
+use serde_json::{json, Value};
+
+use crate::context::{club_of, token, Context, TOKEN_ACCOUNTS_PER_TRANSACTION};
+use crate::output::sent;
+use crate::Result;
+
+#[derive(Subcommand)]
+pub enum GameCommand {
+    /// Open a cash game owned by the signer.
+    Create(CreateArgs),
+    /// Stop new players from sitting down; seated players keep playing.
+    Pause(GameArgs),
+    /// Reopen a paused game.
+    Resume(GameArgs),
+    /// Wind the game down; it closes once the last player is gone.
+    BeginClosing(GameArgs),
+    /// Close an empty game, returning the vault's remainder, rake and rent.
+    Close { game: Pubkey },
+    /// Cash players out with the amounts given, in batches. Anything left
+    /// stays on their seat.
+    Eject {
+        #[command(flatten)]
+        game: GameArgs,
+        /// Who to cash out and for how much.
+        #[arg(required = true, value_name = "PLAYER=AMOUNT", value_parser = parse_ejection)]
+        ejections: Vec<Ejection>,
+    },
+    /// Cash out everyone who has sat out longer than the game allows.
+    EjectSatOut(GameArgs),
+    /// Pay a player back from the vault.
+    Refund {
+        #[command(flatten)]
+        game: GameArgs,
+        player: Pubkey,
+        amount: u64,
+    },
+    /// Pay the accrued rake to the game's rake recipient.
+    WithdrawRake(GameArgs),
+    /// Hand day-to-day operation of the game to another key.
+    SetTransactor {
+        #[command(flatten)]
+        game: GameArgs,
+        transactor: Pubkey,
+    },
+    /// Hold ejected payouts for a challenge window that players can dispute in.
+    SetDisputeSettings {
+        #[command(flatten)]
+        game: GameArgs,
+        /// Seconds payouts are held, zero to pay out straight away.
+        #[arg(long)]
+        window: i64,
+        /// Rules on disputed payouts; needed whenever the window is open.
+        #[arg(long)]
+        arbiter: Option<Pubkey>,
+    },
+    /// Only let players leave on a balance statement they signed.
+    SetSignedCashOuts {
+        #[command(flatten)]
+        game: GameArgs,
+        /// Turn the requirement off again.
+        #[arg(long)]
+        off: bool,
+    },
+    /// Pay out a held settlement once its challenge window has passed.
+    ReleaseSettlement { game: Pubkey, player: Pubkey },
+    /// Rule on a disputed settlement as the game's arbiter.
+    ResolveDispute {
+        game: Pubkey,
+        player: Pubkey,
+        /// What the player is paid, at most what was held.
+        amount: u64,
+    },
+    /// Take a player off the waitlist, refunding their escrowed buy-in.
+    RemoveFromWaitlist {
+        #[command(flatten)]
+        game: GameArgs,
+        player: Pubkey,
+    },
+    /// Start a hand from the committed shuffle round.
+    StartHand(GameArgs),
+    /// Settle the hand in progress, moving chips between seats.
+    SettleHand {
+        #[command(flatten)]
+        game: GameArgs,
+        /// Each seat's winnings, negative for losses, summing to minus the rake.
+        #[arg(
+            required = true,
+            value_name = "PLAYER=DELTA",
+            value_parser = parse_delta,
+            allow_negative_numbers = true
+        )]
+        deltas: Vec<SeatDelta>,
+        #[arg(long, default_value_t = 0)]
+        rake: u64,
+        /// The hand's history hash as 64 hex digits, committed with the settlement.
+        #[arg(long, value_parser = parse_hash)]
+        hand_hash: Option<[u8; 32]>,
+    },
+    /// Commit the history hash of the last settled hand.
+    CommitHandHistory {
+        #[command(flatten)]
+        game: GameArgs,
+        /// 64 hex digits.
+        #[arg(value_parser = parse_hash)]
+        hand_hash: [u8; 32],
+    },
+    /// Pay out a heads-up hand once its seeds are revealed or the reveal
+    /// clock has run out.
+    SettleHeadsUpHand { game: Pubkey },
+}
+
+#[derive(Args)]
+pub struct GameArgs {
+    game: Pubkey,
+    /// Act through a role granted on the game or its club instead of as owner.
+    #[arg(long)]
+    role: bool,
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// Shown to players, at most 32 bytes.
+    #[arg(long, default_value = "")]
+    name: String,
+    /// Chips' mint; native SOL if left out, or the club's default with `--club`.
+    #[arg(long)]
+    mint: Option<Pubkey>,
+    #[arg(long, value_enum, default_value_t = Variant::Nlhe)]
+    variant: Variant,
+    #[arg(long, default_value_t = 9)]
+    max_players: u16,
+    #[arg(long)]
+    small_blind: u64,
+    #[arg(long)]
+    big_blind: u64,
+    #[arg(long, default_value_t = 0)]
+    ante: u64,
+    #[arg(long, default_value_t = 0)]
+    min_deposit: u64,
+    #[arg(long, default_value_t = 0)]
+    max_deposit: u64,
+    /// Buy-in limits in big blinds, replacing the deposits when set.
+    #[arg(long, default_value_t = 0)]
+    min_buy_in_bb: u16,
+    #[arg(long, default_value_t = 0)]
+    max_buy_in_bb: u16,
+    #[arg(long, default_value_t = 0)]
+    rake_bps: u16,
+    #[arg(long, default_value_t = 0)]
+    rake_cap: u64,
+    /// The signer if left out, or the club's default with `--club`.
+    #[arg(long)]
+    rake_recipient: Option<Pubkey>,
+    /// Seconds without operator activity before players can withdraw on their own.
+    #[arg(long, default_value_t = 3600)]
+    inactivity_timeout: i64,
+    /// Seconds a player can sit out before being ejected, zero for no limit.
+    #[arg(long, default_value_t = 0)]
+    max_sit_out_seconds: i64,
+    /// Deal heads-up hands on-chain, timing players out after this many seconds.
+    #[arg(long, value_name = "SECONDS")]
+    heads_up: Option<i64>,
+    /// Runs the table day to day; the signer if left out.
+    #[arg(long)]
+    transactor: Option<Pubkey>,
+    /// Create the game under a club the signer administers.
+    #[arg(long)]
+    club: Option<Pubkey>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Variant {
+    Nlhe,
+    Plo,
+    Plo5,
+    ShortDeck,
+}
+
+impl From<Variant> for GameVariant {
+    fn from(variant: Variant) -> Self {
+        match variant {
+            Variant::Nlhe => GameVariant::Nlhe,
+            Variant::Plo => GameVariant::Plo,
+            Variant::Plo5 => GameVariant::Plo5,
+            Variant::ShortDeck => GameVariant::ShortDeck,
+        }
+    }
+}
+
+fn parse_ejection(arg: &str) -> std::result::Result<Ejection, String> {
+    let (player, amount) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected PLAYER=AMOUNT, got {arg}"))?;
+    Ok(Ejection {
+        player: player.parse().map_err(|e| format!("{player}: {e}"))?,
+        amount: amount.parse().map_err(|e| format!("{amount}: {e}"))?,
+    })
+}
+
+fn parse_delta(arg: &str) -> std::result::Result<SeatDelta, String> {
+    let (player, delta) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected PLAYER=DELTA, got {arg}"))?;
+    Ok(SeatDelta {
+        player: player.parse().map_err(|e| format!("{player}: {e}"))?,
+        delta: delta.parse().map_err(|e| format!("{delta}: {e}"))?,
+    })
+}
+
+fn parse_hash(arg: &str) -> std::result::Result<[u8; 32], String> {
+    let mut hash = [0u8; 32];
+    if arg.len() != 64 || !arg.is_ascii() {
+        return Err(format!("expected 64 hex digits, got {arg}"));
+    }
+    for (byte, digits) in hash.iter_mut().zip(arg.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).map_err(|e| e.to_string())?;
+        *byte = u8::from_str_radix(digits, 16).map_err(|e| format!("{digits}: {e}"))?;
+    }
+    Ok(hash)
+}
+
+/// A game's state along with the addresses derived from it.
+struct Table {
+    address: Pubkey,
+    account: GameAccount,
+    token_program: Pubkey,
+}
+
+impl Table {
+    async fn load(ctx: &Context, address: Pubkey) -> Result<Self> {
+        let account = fetch::game(&ctx.rpc, &address).await?;
+        let token_program = fetch::token_program(&ctx.rpc, &account.token_mint).await?;
+        Ok(Table {
+            address,
+            account,
+            token_program,
+        })
+    }
+
+    fn mint(&self) -> Option<Pubkey> {
+        token(&self.account.token_mint, self.account.token_mint)
+    }
+
+    fn vault_token_account(&self) -> Option<Pubkey> {
+        let mint = &self.account.token_mint;
+        token(
+            mint,
+            pda::vault_token_account(&self.address, mint, &self.token_program),
+        )
+    }
+
+    /// `wallet`'s token account on token tables.
+    fn token_account(&self, wallet: &Pubkey) -> Option<Pubkey> {
+        let mint = &self.account.token_mint;
+        token(mint, pda::payout_address(wallet, mint, &self.token_program))
+    }
+
+    fn club(&self) -> Option<Pubkey> {
+        club_of(&self.account.club)
+    }
+
+    async fn role(&self, ctx: &Context, use_role: bool) -> Result<Option<Pubkey>> {
+        ctx.role(use_role, &self.address, &self.account.club).await
+    }
+
+    fn status_accounts(&self, owner: Pubkey, role: Option<Pubkey>) -> accounts::GameStatusAccounts {
+        accounts::GameStatusAccounts {
+            game_account: self.address,
+            owner,
+            club: self.club(),
+            role,
+            event_authority: pda::event_authority(),
+            program: ID,
+        }
+    }
+
+    fn hand_accounts(&self, payer: Pubkey, role: Option<Pubkey>) -> accounts::SettleHandAccounts {
+        accounts::SettleHandAccounts {
+            game_account: self.address,
+            payer,
+            role,
+            event_authority: pda::event_authority(),
+            program: ID,
+        }
+    }
+
+    fn settlement_accounts(
+        &self,
+        player: Pubkey,
+        payer: Pubkey,
+    ) -> accounts::PaySettlementAccounts {
+        accounts::PaySettlementAccounts {
+            game_account: self.address,
+            game_token_account: self.vault_token_account(),
+            player_token_account: self.token_account(&player),
+            player,
+            payer,
+            pda_account: pda::vault(&self.address),
+            mint: self.mint(),
+            system_program: system_program::ID,
+            token_program: self.token_program,
+            event_authority: pda::event_authority(),
+            program: ID,
+        }
+    }
+}
+
+pub async fn run(ctx: &Context, command: GameCommand) -> Result<Value> {
+    let signer = ctx.pubkey();
+    Ok(match command {
+        GameCommand::Create(args) => create(ctx, args).await?,
+        GameCommand::Pause(args) => set_status(ctx, args, instructions::pause_game).await?,
+        GameCommand::Resume(args) => set_status(ctx, args, instructions::resume_game).await?,
+        GameCommand::BeginClosing(args) => {
+            set_status(ctx, args, instructions::begin_closing_game).await?
+        }
+        GameCommand::Close { game } => {
+            let table = Table::load(ctx, game).await?;
+            let game_account = &table.account;
+            // the remainder and rake are paid out, so both need somewhere to land;
+            let mut batch = ctx
+                .create_token_accounts(
+                    &[game_account.owner, game_account.rake_recipient],
+                    &game_account.token_mint,
+                    &table.token_program,
+                )
+                .await?;
+            batch.push(instructions::close_game(accounts::CloseGame {
+                game_account: game,
+                game_token_account: table.vault_token_account(),
+                owner_token_account: table.token_account(&game_account.owner),
+                rake_recipient_token_account: table.token_account(&game_account.rake_recipient),
+                rake_recipient: game_account.rake_recipient,
+                pda_account: pda::vault(&game),
+                owner: game_account.owner,
+                payer: signer,
+                club: table.club(),
+                mint: table.mint(),
+                system_program: system_program::ID,
+                token_program: table.token_program,
+                event_authority: pda::event_authority(),
+                program: ID,
+            }));
+            sent(&ctx.send(&batch).await?)
+        }
+        GameCommand::Eject { game, ejections } => {
+            let table = Table::load(ctx, game.game).await?;
+            let role = table.role(ctx, game.role).await?;
+            let players: Vec<Pubkey> = ejections.iter().map(|e| e.player).collect();
+            let setup = ctx
+                .create_token_accounts(&players, &table.account.token_mint, &table.token_program)
+                .await?;
+            for batch in setup.chunks(TOKEN_ACCOUNTS_PER_TRANSACTION) {
+                ctx.send(batch).await?;
+            }
+            let signatures =
+                transactions::eject_players(&ctx.rpc, &game.game, &ctx.payer, role, &ejections)
+                    .await?;
+            json!({
+                "signatures": signatures.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            })
+        }
+        GameCommand::EjectSatOut(args) => {
+            let table = Table::load(ctx, args.game).await?;
+            let role = table.role(ctx, args.role).await?;
+            let game_account = &table.account;
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+            let players: Vec<Pubkey> = game_account
+                .players
+                .iter()
+                .filter(|p| {
+                    game_account.max_sit_out_seconds != 0
+                        && p.sitting_out
+                        && now - p.sat_out_at >= game_account.max_sit_out_seconds
+                })
+                .map(|p| p.address)
+                .collect();
+            if players.is_empty() {
+                return Err("nobody has sat out past the game's limit".into());
+            }
+            let mut batch = ctx
+                .create_token_accounts(&players, &game_account.token_mint, &table.token_program)
+                .await?;
+            let mut instruction =
+                instructions::eject_sat_out_players(accounts::EjectSatOutPlayersAccounts {
+                    game_account: table.address,
+                    token_account: table.vault_token_account(),
+                    payer: signer,
+                    role,
+                    pda_account: pda::vault(&table.address),
+                    mint: table.mint(),
+                    system_program: system_program::ID,
+                    token_program: table.token_program,
+                    event_authority: pda::event_authority(),
+                    program: ID,
+                });
+            for player in &players {
+                let payout =
+                    pda::payout_address(player, &game_account.token_mint, &table.token_program);
+                instruction.accounts.push(AccountMeta::new(payout, false));
+            }
+            // the freed seats go to the front of the waitlist;
+            let free =
+                game_account.max_players as usize - game_account.players.len() + players.len();
+            let waitlisted: Vec<Pubkey> =
+                game_account.waitlist.iter().copied().take(free).collect();
+            instruction.accounts.extend(transactions::waitlist_accounts(
+                &table.address,
+                &game_account.token_mint,
+                &table.token_program,
+                &waitlisted,
+            ));
+            batch.push(instruction);
+            let signature = ctx.send(&batch).await?;
+            json!({
+                "signature": signature.to_string(),
+                "players": players.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            })
+        }
+        GameCommand::Refund {
+            game,
+            player,
+            amount,
+        } => {
+            let table = Table::load(ctx, game.game).await?;
+            let role = table.role(ctx, game.role).await?;
+            let mut batch = ctx
+                .create_token_accounts(&[player], &table.account.token_mint, &table.token_program)
+                .await?;
+            batch.push(instructions::refund_player(
+                accounts::RefundPlayerAccounts {
+                    game_account: table.address,
+                    game_token_account: table.vault_token_account(),
+                    player_token_account: table.token_account(&player),
+                    player,
+                    payer: signer,
+                    role,
+                    pda_account: pda::vault(&table.address),
+                    mint: table.mint(),
+                    system_program: system_program::ID,
+                    token_program: table.token_program,
+                    event_authority: pda::event_authority(),
+                    program: ID,
+                },
+                RefundPlayerParams { amount },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        GameCommand::WithdrawRake(args) => {
+            let table = Table::load(ctx, args.game).await?;
+            let role = table.role(ctx, args.role).await?;
+            let recipient = table.account.rake_recipient;
+            let mut batch = ctx
+                .create_token_accounts(
+                    &[recipient],
+                    &table.account.token_mint,
+                    &table.token_program,
+                )
+                .await?;
+            batch.push(instructions::withdraw_rake(
+                accounts::WithdrawRakeAccounts {
+                    game_account: table.address,
+                    game_token_account: table.vault_token_account(),
+                    rake_recipient_token_account: table.token_account(&recipient),
+                    rake_recipient: recipient,
+                    pda_account: pda::vault(&table.address),
+                    payer: signer,
+                    club: table.club(),
+                    role,
+                    mint: table.mint(),
+                    system_program: system_program::ID,
+                    token_program: table.token_program,
+                    event_authority: pda::event_authority(),
+                    program: ID,
+                },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        GameCommand::SetTransactor { game, transactor } => {
+            let table = Table::load(ctx, game.game).await?;
+            let role = table.role(ctx, game.role).await?;
+            let instruction = instructions::set_game_transactor(
+                accounts::SetGameTransactorAccounts {
+                    game_account: table.address,
+                    owner: signer,
+                    club: table.club(),
+                    role,
+                },
+                SetTransactorData { transactor },
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        GameCommand::SetDisputeSettings {
+            game,
+            window,
+            arbiter,
+        } => {
+            let table = Table::load(ctx, game.game).await?;
+            let role = table.role(ctx, game.role).await?;
+            let instruction = instructions::set_dispute_settings(
+                accounts::DisputeSettingsAccounts {
+                    game_account: table.address,
+                    owner: signer,
+                    club: table.club(),
+                    role,
+                },
+                DisputeSettingsData {
+                    challenge_window: window,
+                    arbiter: arbiter.unwrap_or_default(),
+                },
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        GameCommand::SetSignedCashOuts { game, off } => {
+            let table = Table::load(ctx, game.game).await?;
+            let role = table.role(ctx, game.role).await?;
+            let instruction = instructions::set_signed_cash_outs(
+                accounts::SignedCashOutSettingsAccounts {
+                    game_account: table.address,
+                    owner: signer,
+                    club: table.club(),
+                    role,
+                },
+                SignedCashOutSettingsData { required: !off },
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        GameCommand::ReleaseSettlement { game, player } => {
+            let table = Table::load(ctx, game).await?;
+            let mut batch = ctx
+                .create_token_accounts(&[player], &table.account.token_mint, &table.token_program)
+                .await?;
+            batch.push(instructions::release_settlement(
+                table.settlement_accounts(player, signer),
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        GameCommand::ResolveDispute {
+            game,
+            player,
+            amount,
+        } => {
+            let table = Table::load(ctx, game).await?;
+            let mut batch = ctx
+                .create_token_accounts(&[player], &table.account.token_mint, &table.token_program)
+                .await?;
+            batch.push(instructions::resolve_dispute(
+                table.settlement_accounts(player, signer),
+                ResolveDisputeData { amount },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        GameCommand::RemoveFromWaitlist { game, player } => {
+            let table = Table::load(ctx, game.game).await?;
+            let role = table.role(ctx, game.role).await?;
+            let mint = &table.account.token_mint;
+            let mut batch = ctx
+                .create_token_accounts(&[player], mint, &table.token_program)
+                .await?;
+            batch.push(instructions::remove_from_waitlist(
+                accounts::RemoveFromWaitlistAccounts {
+                    game_account: table.address,
+                    waitlist_account: pda::waitlist(&table.address, &player),
+                    escrow_token_account: token(
+                        mint,
+                        pda::waitlist_escrow(&table.address, &player, mint, &table.token_program),
+                    ),
+                    player_token_account: table.token_account(&player),
+                    player,
+                    owner: signer,
+                    club: table.club(),
+                    role,
+                    mint: table.mint(),
+                    system_program: system_program::ID,
+                    token_program: table.token_program,
+                },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        GameCommand::StartHand(args) => {
+            let table = Table::load(ctx, args.game).await?;
+            let role = table.role(ctx, args.role).await?;
+            let instruction = instructions::start_hand(accounts::StartHandAccounts {
+                game_account: table.address,
+                payer: signer,
+                role,
+                shuffle_account: pda::shuffle(&table.address),
+            });
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        GameCommand::SettleHand {
+            game,
+            deltas,
+            rake,
+            hand_hash,
+        } => {
+            let table = Table::load(ctx, game.game).await?;
+            let role = table.role(ctx, game.role).await?;
+            let instruction = instructions::settle_hand(
+                table.hand_accounts(signer, role),
+                SettleHandData {
+                    deltas,
+                    rake,
+                    hand_hash,
+                },
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        GameCommand::CommitHandHistory { game, hand_hash } => {
+            let table = Table::load(ctx, game.game).await?;
+            let role = table.role(ctx, game.role).await?;
+            let instruction = instructions::commit_hand_history(
+                table.hand_accounts(signer, role),
+                CommitHandData { hand_hash },
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        GameCommand::SettleHeadsUpHand { game } => {
+            let instruction =
+                instructions::settle_heads_up_hand(accounts::SettleHeadsUpHandAccounts {
+                    game_account: game,
+                    hand_account: pda::hand(&game),
+                    shuffle_account: pda::shuffle(&game),
+                    payer: signer,
+                    event_authority: pda::event_authority(),
+                    program: ID,
+                });
+            sent(&ctx.send(&[instruction]).await?)
+        }
+    })
+}
+
+async fn set_status(
+    ctx: &Context,
+    args: GameArgs,
+    build: fn(accounts::GameStatusAccounts) -> Instruction,
+) -> Result<Value> {
+    let table = Table::load(ctx, args.game).await?;
+    let role = table.role(ctx, args.role).await?;
+    let instruction = build(table.status_accounts(ctx.pubkey(), role));
+    Ok(sent(&ctx.send(&[instruction]).await?))
+}
+
+async fn create(ctx: &Context, args: CreateArgs) -> Result<Value> {
+    if args.max_deposit == 0 && args.max_buy_in_bb == 0 {
+        return Err("set --max-deposit or --max-buy-in-bb".into());
+    }
+    let owner = ctx.pubkey();
+    let registry = pda::host_registry(&owner);
+    // the registry is created along with the owner's first game;
+    let index = match fetch::account::<HostRegistry>(&ctx.rpc, &registry).await {
+        Ok(registry) => registry.games_created,
+        Err(Error::AccountNotFound(_)) => 0,
+        Err(error) => return Err(error.into()),
+    };
+    let game = pda::game(&owner, index);
+    let mint = match (args.mint, args.club) {
+        (Some(mint), _) => mint,
+        (None, Some(club)) => {
+            fetch::account::<Club>(&ctx.rpc, &club)
+                .await?
+                .default_token_mint
+        }
+        (None, None) => native_mint::ID,
+    };
+    // left unset, the club's default applies, or failing that the owner;
+    let rake_recipient = args.rake_recipient.unwrap_or_default();
+    let token_program = fetch::token_program(&ctx.rpc, &mint).await?;
+    let mut batch = ctx
+        .create_token_accounts(&[pda::vault(&game)], &mint, &token_program)
+        .await?;
+    batch.push(instructions::create_cash_game(
+        accounts::CreateGameParams {
+            host_registry: registry,
+            game_account: game,
+            pda_account: pda::vault(&game),
+            club: args.club,
+            payer: owner,
+            transactor: args.transactor.unwrap_or(owner),
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: ID,
+        },
+        CreateGameData {
+            max_players: args.max_players,
+            min_deposit: args.min_deposit,
+            max_deposit: args.max_deposit,
+            token_mint: mint,
+            rake_bps: args.rake_bps,
+            rake_cap: args.rake_cap,
+            rake_recipient,
+            inactivity_timeout: args.inactivity_timeout,
+            small_blind: args.small_blind,
+            big_blind: args.big_blind,
+            ante: args.ante,
+            variant: args.variant.into(),
+            table_name: args.name,
+            min_buy_in_bb: args.min_buy_in_bb,
+            max_buy_in_bb: args.max_buy_in_bb,
+            max_sit_out_seconds: args.max_sit_out_seconds,
+            mode: match args.heads_up {
+                Some(action_timeout) => GameMode::HeadsUp { action_timeout },
+                None => GameMode::Dealer,
+            },
+        },
+    ));
+    let signature = ctx.send(&batch).await?;
+    Ok(json!({
+        "game": game.to_string(),
+        "signature": signature.to_string(),
+    }))
+}
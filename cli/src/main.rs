@@ -0,0 +1,100 @@
+//! `degods-poker`: operates cash games, tournaments and clubs from the
+//! command line.
+//!
+//! Transactions are signed and paid for by a local keypair, the Solana CLI's
+//! by default, and sent to `--url`, a local test validator unless told
+//! otherwise. Amounts are in the mint's base units. `show` prints decoded
+//! account state; `--json` switches every command to JSON for scripting.
+//!
+//! It covers the operator's side of the program. What players sign for
+//! themselves (joining, leaving, betting, disputes) and the dealer's
+//! shuffle seeds and cards are left to their wallets and dealing software.
+
+mod club;
+mod context;
+mod game;
+mod output;
+mod role;
+mod show;
+mod tournament;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use serde_json::Value;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use crate::context::Context;
+use crate::output::Format;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "degods-poker",
+    version,
+    about = "Operate degods-poker games and tournaments"
+)]
+struct Cli {
+    /// RPC endpoint.
+    #[arg(long, short, global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Keypair that signs and pays; ~/.config/solana/id.json if left out.
+    #[arg(long, short, global = true)]
+    keypair: Option<PathBuf>,
+    /// Print JSON instead of text.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create and run cash games.
+    #[command(subcommand)]
+    Game(game::GameCommand),
+    /// Create and run tournaments.
+    #[command(subcommand)]
+    Tournament(tournament::TournamentCommand),
+    /// Create and run clubs.
+    #[command(subcommand)]
+    Club(club::ClubCommand),
+    /// Delegate games, tournaments and clubs to other keys.
+    #[command(subcommand)]
+    Role(role::RoleCommand),
+    /// Print an account's decoded state.
+    #[command(subcommand)]
+    Show(show::ShowCommand),
+}
+
+async fn run(cli: Cli) -> Result<Value> {
+    let rpc = RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed());
+    match cli.command {
+        Command::Game(command) => game::run(&Context::new(rpc, cli.keypair)?, command).await,
+        Command::Tournament(command) => {
+            tournament::run(&Context::new(rpc, cli.keypair)?, command).await
+        }
+        Command::Club(command) => club::run(&Context::new(rpc, cli.keypair)?, command).await,
+        Command::Role(command) => role::run(&Context::new(rpc, cli.keypair)?, command).await,
+        Command::Show(command) => show::run(&rpc, command).await,
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let format = if cli.json { Format::Json } else { Format::Text };
+    match run(cli).await {
+        Ok(value) => {
+            output::print(format, &value);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use serde_json::{json, Value};
+use solana_sdk::signature::Signature;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Indented `key: value` lines for people.
+    Text,
+    /// One JSON document for scripts.
+    Json,
+}
+
+pub fn print(format: Format, value: &Value) {
+    match format {
+        Format::Json => println!("{value}"),
+        Format::Text => {
+            let mut out = String::new();
+            render(&mut out, value, 0);
+            print!("{out}");
+        }
+    }
+}
+
+/// Renders `value` as text, nesting objects and lists of objects under
+/// their key.
+pub fn render(out: &mut String, value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                match field {
+                    Value::Object(inner) if !inner.is_empty() => {
+                        out.push_str(&format!("{pad}{key}:\n"));
+                        render(out, field, indent + 2);
+                    }
+                    Value::Array(items) if items.iter().any(Value::is_object) => {
+                        out.push_str(&format!("{pad}{key}:\n"));
+                        for item in items {
+                            out.push_str(&format!("{pad}  -\n"));
+                            render(out, item, indent + 4);
+                        }
+                    }
+                    _ => out.push_str(&format!("{pad}{key}: {}\n", scalar(field))),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                render(out, item, indent);
+            }
+        }
+        _ => out.push_str(&format!("{pad}{}\n", scalar(value))),
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "-".to_string(),
+        Value::Array(items) if items.is_empty() => "none".to_string(),
+        Value::Array(items) => items.iter().map(scalar).collect::<Vec<_>>().join(", "),
+        Value::Object(_) => "{}".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// What a command that sent one transaction prints.
+pub fn sent(signature: &Signature) -> Value {
+    json!({ "signature": signature.to_string() })
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use clap::{Subcommand, ValueEnum};
+use degods_poker::state::Role;
+use degods_poker_client::{accounts, instructions, pda};
+use serde_json::Value;
+
+use crate::context::Context;
+use crate::output::sent;
+use crate::Result;
+
+#[derive(Subcommand)]
+pub enum RoleCommand {
+    /// Delegate part of a game, tournament or club the signer owns. Granting
+    /// again replaces the grantee's role.
+    Grant {
+        /// The game, tournament or club; a club role covers everything in it.
+        scope: Pubkey,
+        grantee: Pubkey,
+        #[arg(value_enum)]
+        role: RoleArg,
+    },
+    /// Take a role back, returning its rent to the signer.
+    Revoke { scope: Pubkey, grantee: Pubkey },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RoleArg {
+    /// Changes configuration: transactor, status, payouts, registration.
+    Admin,
+    /// Runs hands, takes rake, ejects and busts players.
+    Dealer,
+    /// Refunds players and withdraws rake.
+    Cashier,
+}
+
+impl From<RoleArg> for Role {
+    fn from(role: RoleArg) -> Self {
+        match role {
+            RoleArg::Admin => Role::Admin,
+            RoleArg::Dealer => Role::Dealer,
+            RoleArg::Cashier => Role::Cashier,
+        }
+    }
+}
+
+pub async fn run(ctx: &Context, command: RoleCommand) -> Result<Value> {
+    let signer = ctx.pubkey();
+    let instruction = match command {
+        RoleCommand::Grant {
+            scope,
+            grantee,
+            role,
+        } => instructions::grant_role(
+            accounts::GrantRoleAccounts {
+                role_account: pda::role(&scope, &grantee),
+                scope,
+                owner: signer,
+                system_program: system_program::ID,
+            },
+            grantee,
+            role.into(),
+        ),
+        RoleCommand::Revoke { scope, grantee } => {
+            instructions::revoke_role(accounts::RevokeRoleAccounts {
+                role_account: pda::role(&scope, &grantee),
+                scope,
+                owner: signer,
+            })
+        }
+    };
+    Ok(sent(&ctx.send(&[instruction]).await?))
+}
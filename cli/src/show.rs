@@ -0,0 +1,217 @@
+use anchor_lang::prelude::Pubkey;
+use clap::Subcommand;
+use degods_poker::state::{
+    GameAccount, GameMode, HandAccount, HostRegistry, PendingSettlement, SeatedPlayer,
+    TournamentAccount, TournamentPlayerAccount,
+};
+use degods_poker_client::{fetch, pda, Error};
+use serde_json::{json, Value};
+use solana_client::nonblocking::rpc_client::RpcClient;
+
+use crate::Result;
+
+#[derive(Subcommand)]
+pub enum ShowCommand {
+    /// A cash game: settings, seats and held payouts.
+    Game { game: Pubkey },
+    /// The heads-up hand being played at a game.
+    Hand { game: Pubkey },
+    /// A tournament: entries, payout table and NFT prizes.
+    Tournament { tournament: Pubkey },
+    /// A player's registration in a tournament.
+    TournamentPlayer { tournament: Pubkey, player: Pubkey },
+    /// The games and tournaments a wallet has created.
+    Host { owner: Pubkey },
+}
+
+pub async fn run(rpc: &RpcClient, command: ShowCommand) -> Result<Value> {
+    Ok(match command {
+        ShowCommand::Game { game } => game_json(&game, &fetch::game(rpc, &game).await?),
+        ShowCommand::Hand { game } => {
+            let address = pda::hand(&game);
+            hand_json(&address, &fetch::account(rpc, &address).await?)
+        }
+        ShowCommand::Tournament { tournament } => {
+            tournament_json(&tournament, &fetch::tournament(rpc, &tournament).await?)
+        }
+        ShowCommand::TournamentPlayer { tournament, player } => tournament_player_json(
+            &pda::tournament_player(&tournament, &player),
+            &fetch::tournament_player(rpc, &tournament, &player).await?,
+        ),
+        ShowCommand::Host { owner } => {
+            let address = pda::host_registry(&owner);
+            // a wallet that never created anything has no registry yet;
+            let (games, tournaments) = match fetch::account::<HostRegistry>(rpc, &address).await {
+                Ok(registry) => (registry.games_created, registry.tournaments_created),
+                Err(Error::AccountNotFound(_)) => (0, 0),
+                Err(error) => return Err(error.into()),
+            };
+            json!({
+                "owner": owner.to_string(),
+                "games": (0..games).map(|i| pda::game(&owner, i).to_string()).collect::<Vec<_>>(),
+                "tournaments": (0..tournaments)
+                    .map(|i| pda::tournament(&owner, i).to_string())
+                    .collect::<Vec<_>>(),
+            })
+        }
+    })
+}
+
+pub fn game_json(address: &Pubkey, game: &GameAccount) -> Value {
+    json!({
+        "address": address.to_string(),
+        "name": game.table_name,
+        "owner": game.owner.to_string(),
+        "transactor": game.transactor.to_string(),
+        "club": optional(&game.club),
+        "status": format!("{:?}", game.status),
+        "variant": format!("{:?}", game.variant),
+        "mode": match game.mode {
+            GameMode::Dealer => json!("Dealer"),
+            GameMode::HeadsUp { action_timeout } => json!({ "heads_up": { "action_timeout": action_timeout } }),
+        },
+        "token_mint": game.token_mint.to_string(),
+        "vault": pda::vault(address).to_string(),
+        "max_players": game.max_players,
+        "small_blind": game.small_blind,
+        "big_blind": game.big_blind,
+        "ante": game.ante,
+        "min_deposit": game.min_deposit,
+        "max_deposit": game.max_deposit,
+        "min_buy_in_bb": game.min_buy_in_bb,
+        "max_buy_in_bb": game.max_buy_in_bb,
+        "rake_bps": game.rake_bps,
+        "rake_cap": game.rake_cap,
+        "rake_recipient": game.rake_recipient.to_string(),
+        "rake_accrued": game.rake_accrued,
+        "hand_in_progress": game.hand_in_progress,
+        "hands_committed": game.hands_committed,
+        "last_activity": game.last_activity,
+        "inactivity_timeout": game.inactivity_timeout,
+        "max_sit_out_seconds": game.max_sit_out_seconds,
+        "challenge_window": game.challenge_window,
+        "arbiter": optional(&game.arbiter),
+        "signed_cash_outs": game.signed_cash_outs,
+        "statement_nonce": game.statement_nonce,
+        "players": game.players.iter().map(seat_json).collect::<Vec<_>>(),
+        "waitlist": game.waitlist.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+        "pending_settlements": game
+            .pending_settlements
+            .iter()
+            .map(settlement_json)
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn seat_json(seat: &SeatedPlayer) -> Value {
+    json!({
+        "seat": seat.seat,
+        "address": seat.address.to_string(),
+        "balance": seat.balance,
+        "deposited": seat.deposited,
+        "withdrawn": seat.withdrawn,
+        "sitting_out": seat.sitting_out,
+        "sat_out_at": seat.sat_out_at,
+        "nonce": seat.nonce,
+    })
+}
+
+fn settlement_json(settlement: &PendingSettlement) -> Value {
+    json!({
+        "player": settlement.player.to_string(),
+        "amount": settlement.amount,
+        "held": settlement.held,
+        "release_at": settlement.release_at,
+        "disputed": settlement.disputed,
+        "claimed": settlement.claimed,
+        "evidence": hex(&settlement.evidence),
+        "nonce": settlement.nonce,
+    })
+}
+
+pub fn hand_json(address: &Pubkey, hand: &HandAccount) -> Value {
+    let seats: Vec<Value> = (0..2)
+        .map(|i| {
+            json!({
+                "player": hand.players[i].to_string(),
+                "stack": hand.stacks[i],
+                "committed": hand.committed[i],
+                "street_bet": hand.street_bets[i],
+                "hole_cards": if hand.hole_cards_revealed[i] {
+                    hand.hole_cards[i].iter().map(|c| card(*c)).collect::<Vec<_>>().join(" ")
+                } else {
+                    "?? ??".to_string()
+                },
+            })
+        })
+        .collect();
+    json!({
+        "address": address.to_string(),
+        "game": hand.game.to_string(),
+        "hand": hand.hand,
+        "street": format!("{:?}", hand.street),
+        "to_act": hand.to_act,
+        "betting_closed": hand.betting_closed,
+        "action_deadline": hand.action_deadline,
+        "big_blind": hand.big_blind,
+        "last_raise": hand.last_raise,
+        "board": hand.board.iter().map(|c| card(*c)).collect::<Vec<_>>(),
+        "outcome": hand.outcome.map(|outcome| format!("{outcome:?}")),
+        "players": seats,
+    })
+}
+
+pub fn tournament_json(address: &Pubkey, tournament: &TournamentAccount) -> Value {
+    json!({
+        "address": address.to_string(),
+        "owner": tournament.owner.to_string(),
+        "transactor": tournament.transactor.to_string(),
+        "club": optional(&tournament.club),
+        "token_mint": tournament.token_mint.to_string(),
+        "vault": pda::vault(address).to_string(),
+        "registration_open": tournament.registration_open,
+        "has_started": tournament.has_started,
+        "players": tournament.players,
+        "players_with_rebuys": tournament.players_with_rebuys,
+        "min_players": tournament.min_players,
+        "max_players": tournament.max_players,
+        "entry_cost": tournament.entry_cost,
+        "entry_fee": tournament.entry_fee,
+        "guarantee": tournament.guarantee,
+        "payouts": tournament.payouts,
+        "nft_payouts": tournament.nft_payouts,
+    })
+}
+
+pub fn tournament_player_json(address: &Pubkey, player: &TournamentPlayerAccount) -> Value {
+    json!({
+        "address": address.to_string(),
+        "has_busted": player.has_busted,
+        "position_finished": player.position_finished,
+        "rebuys": player.rebuys,
+        "paid": player.paid,
+    })
+}
+
+/// Null for the default key the program stores when something is unset.
+fn optional(key: &Pubkey) -> Value {
+    if *key == Pubkey::default() {
+        Value::Null
+    } else {
+        json!(key.to_string())
+    }
+}
+
+/// A card as rank and suit, e.g. `As` or `Td`.
+fn card(card: u8) -> String {
+    let ranks = b"23456789TJQKA";
+    let suits = b"cdhs";
+    match (ranks.get(card as usize / 4), suits.get(card as usize % 4)) {
+        (Some(rank), Some(suit)) => format!("{}{}", *rank as char, *suit as char),
+        _ => format!("#{card}"),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
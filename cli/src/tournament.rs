@@ -0,0 +1,408 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+use clap::{Args, Subcommand};
+use degods_poker::state::*;
+use degods_poker_client::{accounts, fetch, instructions, pda, Error, ID};
+use serde_json::{json, Value};
+
+use crate::context::{club_of, token, Context};
+use crate::output::sent;
+use crate::Result;
+
+#[derive(Subcommand)]
+pub enum TournamentCommand {
+    /// Open a tournament owned by the signer.
+    Create(CreateArgs),
+    /// Replace the payout table.
+    SetPayouts {
+        #[command(flatten)]
+        tournament: TournamentArgs,
+        /// Each place's share of the prize pool in tenths of a percent, first place first.
+        #[arg(required = true, value_delimiter = ',')]
+        payouts: Vec<u16>,
+    },
+    /// Open registration if it's closed, close it if it's open.
+    FlipRegistration(TournamentArgs),
+    /// Start play once at least two players have registered.
+    Start(TournamentArgs),
+    /// Bust a player out, paying them if they finished in the money.
+    Payout {
+        #[command(flatten)]
+        tournament: TournamentArgs,
+        player: Pubkey,
+    },
+    /// Refund a player's entry and drop their registration.
+    Refund {
+        #[command(flatten)]
+        tournament: TournamentArgs,
+        player: Pubkey,
+    },
+    /// Close a finished tournament, returning what's left in the vault.
+    Close(TournamentArgs),
+    /// Escrow an NFT paid to the player finishing in `place`.
+    AddNftPrize {
+        #[command(flatten)]
+        tournament: TournamentArgs,
+        place: u16,
+        nft_mint: Pubkey,
+    },
+    /// Take an escrowed NFT prize back to the owner before the tournament starts.
+    RemoveNftPrize {
+        #[command(flatten)]
+        tournament: TournamentArgs,
+        place: u16,
+        nft_mint: Pubkey,
+    },
+}
+
+#[derive(Args)]
+pub struct TournamentArgs {
+    tournament: Pubkey,
+    /// Act through a role granted on the tournament or its club instead of as owner.
+    #[arg(long)]
+    role: bool,
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    #[arg(long)]
+    max_players: u16,
+    /// What each entry adds to the prize pool.
+    #[arg(long)]
+    entry_cost: u64,
+    /// Charged on top of the entry cost and kept by the host.
+    #[arg(long, default_value_t = 0)]
+    entry_fee: u64,
+    /// Native SOL if left out, or the club's default with `--club`.
+    #[arg(long)]
+    mint: Option<Pubkey>,
+    /// Deposited now from the signer; the prize pool is never smaller.
+    #[arg(long, default_value_t = 0)]
+    guarantee: u64,
+    /// Each place's share of the prize pool in tenths of a percent, first place first.
+    #[arg(long, value_delimiter = ',', default_value = "1000")]
+    payouts: Vec<u16>,
+    /// Open registration straight away.
+    #[arg(long)]
+    open: bool,
+    /// Runs the tournament day to day; the signer if left out.
+    #[arg(long)]
+    transactor: Option<Pubkey>,
+    /// Create the tournament under a club the signer administers.
+    #[arg(long)]
+    club: Option<Pubkey>,
+}
+
+/// A tournament's state along with the addresses derived from it.
+struct Event {
+    address: Pubkey,
+    account: TournamentAccount,
+    token_program: Pubkey,
+}
+
+impl Event {
+    async fn load(ctx: &Context, address: Pubkey) -> Result<Self> {
+        let account = fetch::tournament(&ctx.rpc, &address).await?;
+        let token_program = fetch::token_program(&ctx.rpc, &account.token_mint).await?;
+        Ok(Event {
+            address,
+            account,
+            token_program,
+        })
+    }
+
+    fn mint(&self) -> Option<Pubkey> {
+        token(&self.account.token_mint, self.account.token_mint)
+    }
+
+    fn vault_token_account(&self) -> Option<Pubkey> {
+        let mint = &self.account.token_mint;
+        token(
+            mint,
+            pda::vault_token_account(&self.address, mint, &self.token_program),
+        )
+    }
+
+    /// `wallet`'s token account on token tournaments.
+    fn token_account(&self, wallet: &Pubkey) -> Option<Pubkey> {
+        let mint = &self.account.token_mint;
+        token(mint, pda::payout_address(wallet, mint, &self.token_program))
+    }
+
+    fn club(&self) -> Option<Pubkey> {
+        club_of(&self.account.club)
+    }
+
+    async fn role(&self, ctx: &Context, use_role: bool) -> Result<Option<Pubkey>> {
+        ctx.role(use_role, &self.address, &self.account.club).await
+    }
+}
+
+pub async fn run(ctx: &Context, command: TournamentCommand) -> Result<Value> {
+    let signer = ctx.pubkey();
+    Ok(match command {
+        TournamentCommand::Create(args) => create(ctx, args).await?,
+        TournamentCommand::SetPayouts {
+            tournament,
+            payouts,
+        } => {
+            let event = Event::load(ctx, tournament.tournament).await?;
+            let role = event.role(ctx, tournament.role).await?;
+            let instruction = instructions::update_tournament_payouts(
+                accounts::UpdateTournamentPayoutsParams {
+                    tournament_account: event.address,
+                    owner: signer,
+                    system_program: system_program::ID,
+                    club: event.club(),
+                    role,
+                },
+                UpdateTournamentPayoutData { payouts },
+            );
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        TournamentCommand::FlipRegistration(args) => {
+            let event = Event::load(ctx, args.tournament).await?;
+            let role = event.role(ctx, args.role).await?;
+            let instruction = instructions::flip_tournament_registration(
+                accounts::FlipTournamentRegistrationParams {
+                    tournament_account: event.address,
+                    owner: signer,
+                    system_program: system_program::ID,
+                    club: event.club(),
+                    role,
+                    event_authority: pda::event_authority(),
+                    program: ID,
+                },
+            );
+            let signature = ctx.send(&[instruction]).await?;
+            json!({
+                "signature": signature.to_string(),
+                "registration_open": !event.account.registration_open,
+            })
+        }
+        TournamentCommand::Start(args) => {
+            let event = Event::load(ctx, args.tournament).await?;
+            let role = event.role(ctx, args.role).await?;
+            let instruction = instructions::start_tournament(accounts::StartTournamentParams {
+                tournament_account: event.address,
+                owner: signer,
+                system_program: system_program::ID,
+                club: event.club(),
+                role,
+                event_authority: pda::event_authority(),
+                program: ID,
+            });
+            sent(&ctx.send(&[instruction]).await?)
+        }
+        TournamentCommand::Payout { tournament, player } => {
+            let event = Event::load(ctx, tournament.tournament).await?;
+            let role = event.role(ctx, tournament.role).await?;
+            let mut batch = ctx
+                .create_token_accounts(&[player], &event.account.token_mint, &event.token_program)
+                .await?;
+            batch.push(instructions::payout_tournament_player(
+                accounts::BustTournamentParams {
+                    tournament_account: event.address,
+                    tournament_player_account: pda::tournament_player(&event.address, &player),
+                    tournament_token_account: event.vault_token_account(),
+                    player_token_account: event.token_account(&player),
+                    pda_account: pda::vault(&event.address),
+                    owner: signer,
+                    player,
+                    mint: event.mint(),
+                    system_program: system_program::ID,
+                    token_program: event.token_program,
+                    club: event.club(),
+                    role,
+                    event_authority: pda::event_authority(),
+                    program: ID,
+                },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        TournamentCommand::Refund { tournament, player } => {
+            let event = Event::load(ctx, tournament.tournament).await?;
+            let role = event.role(ctx, tournament.role).await?;
+            let mut batch = ctx
+                .create_token_accounts(&[player], &event.account.token_mint, &event.token_program)
+                .await?;
+            batch.push(instructions::refund_tournament(
+                accounts::RefundTournamentParams {
+                    tournament_account: event.address,
+                    tournament_player_account: pda::tournament_player(&event.address, &player),
+                    tournament_token_account: event.vault_token_account(),
+                    player_token_account: event.token_account(&player),
+                    pda_account: pda::vault(&event.address),
+                    owner: signer,
+                    player,
+                    mint: event.mint(),
+                    system_program: system_program::ID,
+                    token_program: event.token_program,
+                    club: event.club(),
+                    role,
+                    event_authority: pda::event_authority(),
+                    program: ID,
+                },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        TournamentCommand::Close(args) => {
+            let event = Event::load(ctx, args.tournament).await?;
+            let role = event.role(ctx, args.role).await?;
+            let owner = event.account.owner;
+            let mut batch = ctx
+                .create_token_accounts(&[owner], &event.account.token_mint, &event.token_program)
+                .await?;
+            batch.push(instructions::close_tournament(
+                accounts::CloseTournamentParams {
+                    tournament_account: event.address,
+                    tournament_token_account: event.vault_token_account(),
+                    owner_token_account: event.token_account(&owner),
+                    pda_account: pda::vault(&event.address),
+                    owner,
+                    payer: signer,
+                    mint: event.mint(),
+                    token_program: event.token_program,
+                    system_program: system_program::ID,
+                    club: event.club(),
+                    role,
+                    event_authority: pda::event_authority(),
+                    program: ID,
+                },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        TournamentCommand::AddNftPrize {
+            tournament,
+            place,
+            nft_mint,
+        } => {
+            let event = Event::load(ctx, tournament.tournament).await?;
+            let role = event.role(ctx, tournament.role).await?;
+            let token_program = fetch::token_program(&ctx.rpc, &nft_mint).await?;
+            let escrow = pda::nft_prize(&event.address, place);
+            let mut batch = ctx
+                .create_token_accounts(&[escrow], &nft_mint, &token_program)
+                .await?;
+            batch.push(instructions::add_nft_tournament_prize(
+                accounts::AddNFTTournamentPrizeParams {
+                    tournament_account: event.address,
+                    tournament_nft_payout_account: escrow,
+                    tournament_nft_token_account: pda::payout_address(
+                        &escrow,
+                        &nft_mint,
+                        &token_program,
+                    ),
+                    owner_nft_token_account: pda::payout_address(
+                        &signer,
+                        &nft_mint,
+                        &token_program,
+                    ),
+                    owner: signer,
+                    nft_mint,
+                    system_program: system_program::ID,
+                    token_program,
+                    club: event.club(),
+                    role,
+                },
+                NftTournamentPrizeData { place_paid: place },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+        TournamentCommand::RemoveNftPrize {
+            tournament,
+            place,
+            nft_mint,
+        } => {
+            let event = Event::load(ctx, tournament.tournament).await?;
+            let role = event.role(ctx, tournament.role).await?;
+            let token_program = fetch::token_program(&ctx.rpc, &nft_mint).await?;
+            let escrow = pda::nft_prize(&event.address, place);
+            let owner = event.account.owner;
+            let mut batch = ctx
+                .create_token_accounts(&[owner], &nft_mint, &token_program)
+                .await?;
+            batch.push(instructions::remove_nft_tournament_prize(
+                accounts::RemoveNftTournamentPrizeParams {
+                    tournament_account: event.address,
+                    tournament_nft_payout_account: escrow,
+                    tournament_nft_token_account: pda::payout_address(
+                        &escrow,
+                        &nft_mint,
+                        &token_program,
+                    ),
+                    owner_nft_token_account: pda::payout_address(&owner, &nft_mint, &token_program),
+                    owner: signer,
+                    nft_mint,
+                    system_program: system_program::ID,
+                    token_program,
+                    club: event.club(),
+                    role,
+                },
+                NftTournamentPrizeData { place_paid: place },
+            ));
+            sent(&ctx.send(&batch).await?)
+        }
+    })
+}
+
+async fn create(ctx: &Context, args: CreateArgs) -> Result<Value> {
+    let owner = ctx.pubkey();
+    let registry = pda::host_registry(&owner);
+    // the registry is created along with the owner's first game or tournament;
+    let index = match fetch::account::<HostRegistry>(&ctx.rpc, &registry).await {
+        Ok(registry) => registry.tournaments_created,
+        Err(Error::AccountNotFound(_)) => 0,
+        Err(error) => return Err(error.into()),
+    };
+    let tournament = pda::tournament(&owner, index);
+    let mint = match (args.mint, args.club) {
+        (Some(mint), _) => mint,
+        (None, Some(club)) => {
+            fetch::account::<Club>(&ctx.rpc, &club)
+                .await?
+                .default_token_mint
+        }
+        (None, None) => native_mint::ID,
+    };
+    let token_program = fetch::token_program(&ctx.rpc, &mint).await?;
+    let vault = pda::vault(&tournament);
+    let mut batch = ctx
+        .create_token_accounts(&[vault], &mint, &token_program)
+        .await?;
+    batch.push(instructions::create_tournament(
+        accounts::CreateTournamentParams {
+            host_registry: registry,
+            tournament_account: tournament,
+            owner,
+            club: args.club,
+            transactor: args.transactor.unwrap_or(owner),
+            pda_account: vault,
+            owner_token_account: token(&mint, pda::payout_address(&owner, &mint, &token_program)),
+            tournament_token_account: token(
+                &mint,
+                pda::vault_token_account(&tournament, &mint, &token_program),
+            ),
+            mint: token(&mint, mint),
+            system_program: system_program::ID,
+            token_program,
+            event_authority: pda::event_authority(),
+            program: ID,
+        },
+        CreateTournamentData {
+            max_players: args.max_players,
+            entry_fee: args.entry_fee,
+            entry_cost: args.entry_cost,
+            token_mint: mint,
+            registration_open: args.open,
+            initial_payouts: args.payouts,
+            guarantee: args.guarantee,
+        },
+    ));
+    let signature = ctx.send(&batch).await?;
+    Ok(json!({
+        "tournament": tournament.to_string(),
+        "signature": signature.to_string(),
+    }))
+}
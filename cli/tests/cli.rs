@@ -0,0 +1,75 @@
+use std::process::{Command, Output};
+
+const GAME: &str = "11111111111111111111111111111111";
+
+fn degods_poker(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_degods-poker"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn help_lists_the_command_groups() {
+    let output = degods_poker(&["--help"]);
+    assert!(output.status.success());
+    let help = String::from_utf8(output.stdout).unwrap();
+    for group in [
+        "game",
+        "tournament",
+        "club",
+        "role",
+        "show",
+        "--json",
+        "--keypair",
+        "--url",
+    ] {
+        assert!(help.contains(group), "{group} missing from:\n{help}");
+    }
+}
+
+#[test]
+fn ejections_need_a_player_and_an_amount() {
+    let output = degods_poker(&["game", "eject", GAME, &format!("{GAME}:5")]);
+    assert_eq!(output.status.code(), Some(2));
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("PLAYER=AMOUNT"), "{error}");
+}
+
+#[test]
+fn hand_settlements_take_signed_deltas_and_a_hex_hash() {
+    let settle = |hash: &str| {
+        degods_poker(&[
+            "game",
+            "settle-hand",
+            GAME,
+            &format!("{GAME}=-5"),
+            "--hand-hash",
+            hash,
+            "--keypair",
+            "/nonexistent/id.json",
+        ])
+    };
+    // getting as far as the keypair means the arguments parsed;
+    let output = settle(&"ab".repeat(32));
+    assert_eq!(output.status.code(), Some(1));
+    let output = settle("abc");
+    assert_eq!(output.status.code(), Some(2));
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("64 hex digits"), "{error}");
+}
+
+#[test]
+fn failures_exit_non_zero_with_a_message() {
+    // nothing listens on port 1;
+    let output = degods_poker(&["show", "game", GAME, "--url", "http://127.0.0.1:1"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("error: "));
+    let output = degods_poker(&["game", "pause", GAME, "--keypair", "/nonexistent/id.json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("can't read keypair"), "{error}");
+}
@@ -0,0 +1,134 @@
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::prelude::Pubkey;
+use degods_poker::PokerError;
+use solana_client::client_error::ClientError;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The program rejected the transaction.
+    #[error("{}: {0}", .0.name())]
+    Program(PokerError),
+    /// Any other custom error code, e.g. an Anchor constraint or a token
+    /// program failure.
+    #[error("custom program error {0:#x}")]
+    Custom(u32),
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account {0} isn't the expected type")]
+    InvalidAccount(Pubkey),
+    #[error(transparent)]
+    Rpc(Box<ClientError>),
+}
+
+impl From<ClientError> for Error {
+    fn from(error: ClientError) -> Self {
+        match error.get_transaction_error() {
+            Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+                match poker_error(code) {
+                    Some(error) => Error::Program(error),
+                    None => Error::Custom(code),
+                }
+            }
+            _ => Error::Rpc(Box::new(error)),
+        }
+    }
+}
+
+/// In declaration order, so `POKER_ERRORS[i]` has code `ERROR_CODE_OFFSET + i`.
+/// New variants go at the end; `tests/client.rs` checks this against the program's enum.
+const POKER_ERRORS: [PokerError; 84] = [
+    PokerError::IncorrectTokenOwner,
+    PokerError::InitialTokenAccountBalanceNonZero,
+    PokerError::NotEnoughFunds,
+    PokerError::GameFull,
+    PokerError::AlreadyAtTable,
+    PokerError::DepositTooSmall,
+    PokerError::DepositTooLarge,
+    PokerError::NotGameOwner,
+    PokerError::NotAtTable,
+    PokerError::GameNotActive,
+    PokerError::InvalidAddress,
+    PokerError::PlayersStillAtTable,
+    PokerError::AlreadyInTournament,
+    PokerError::TournamentAlreadyStarted,
+    PokerError::TournamentNotStarted,
+    PokerError::NotEnoughPlayersToStartTournament,
+    PokerError::InvalidPayoutsArray,
+    PokerError::CantBustLastPlayerTournament,
+    PokerError::NFTsEscrowedInTournament,
+    PokerError::SeatBalanceTooLow,
+    PokerError::VaultUnderfunded,
+    PokerError::InvalidRake,
+    PokerError::UnbalancedSettlement,
+    PokerError::RakeTooLarge,
+    PokerError::DuplicatePlayer,
+    PokerError::NotGameOperator,
+    PokerError::HandInProgress,
+    PokerError::InvalidInactivityTimeout,
+    PokerError::GameNotAbandoned,
+    PokerError::InvalidGameStatus,
+    PokerError::InvalidStakes,
+    PokerError::TableNameTooLong,
+    PokerError::WaitlistFull,
+    PokerError::TableNotFull,
+    PokerError::NotNextOnWaitlist,
+    PokerError::InvalidSeat,
+    PokerError::SeatTaken,
+    PokerError::SitOutLimitNotReached,
+    PokerError::MintMismatch,
+    PokerError::NotClubAdmin,
+    PokerError::ClubNameTooLong,
+    PokerError::TooManyClubAdmins,
+    PokerError::ClubAllowlistFull,
+    PokerError::NotOnAllowlist,
+    PokerError::ClubRequired,
+    PokerError::InvalidRoleScope,
+    PokerError::NotScopeOwner,
+    PokerError::InvalidShufflePhase,
+    PokerError::AlreadyCommitted,
+    PokerError::MissingShuffleCommitment,
+    PokerError::NotShuffleParty,
+    PokerError::ShuffleSeedMismatch,
+    PokerError::InvalidGameMode,
+    PokerError::NeedTwoPlayers,
+    PokerError::NotYourTurn,
+    PokerError::InvalidAction,
+    PokerError::InvalidBet,
+    PokerError::InvalidStreet,
+    PokerError::CardMismatch,
+    PokerError::ActionClockRunning,
+    PokerError::InvalidChallengeWindow,
+    PokerError::InvalidArbiter,
+    PokerError::TooManyPendingSettlements,
+    PokerError::NoPendingSettlement,
+    PokerError::AlreadyDisputed,
+    PokerError::ChallengeWindowClosed,
+    PokerError::ChallengeWindowOpen,
+    PokerError::PayoutDisputed,
+    PokerError::NotDisputed,
+    PokerError::NotArbiter,
+    PokerError::InvalidSettlementAmount,
+    PokerError::SettlementsPending,
+    PokerError::SignedCashOutRequired,
+    PokerError::MissingStatementSignature,
+    PokerError::StaleStatement,
+    PokerError::SeatMismatch,
+    PokerError::EjectionAmountsMismatch,
+    PokerError::AmountOverflow,
+    PokerError::TooManySeats,
+    PokerError::ShufflePartiesFull,
+    PokerError::DealerSeedMissing,
+    PokerError::RevealWindowOpen,
+    PokerError::WaitlistAccountsMissing,
+    PokerError::NoHandInProgress,
+];
+
+/// The `PokerError` a custom instruction error code stands for.
+pub fn poker_error(code: u32) -> Option<PokerError> {
+    let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+    POKER_ERRORS.get(index as usize).copied()
+}
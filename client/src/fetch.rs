@@ -0,0 +1,47 @@
+//! Reads program accounts over RPC.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token;
+use degods_poker::state::{GameAccount, TournamentAccount, TournamentPlayerAccount};
+use degods_poker::vault::is_native;
+use solana_client::nonblocking::rpc_client::RpcClient;
+
+use crate::{pda, Error, Result};
+
+/// Fetches and decodes any program account, checking its discriminator.
+pub async fn account<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+    let response = rpc
+        .get_account_with_commitment(address, rpc.commitment())
+        .await?;
+    let account = match response.value {
+        Some(account) => account,
+        None => return Err(Error::AccountNotFound(*address)),
+    };
+    T::try_deserialize(&mut account.data.as_slice()).map_err(|_| Error::InvalidAccount(*address))
+}
+
+pub async fn game(rpc: &RpcClient, game: &Pubkey) -> Result<GameAccount> {
+    account(rpc, game).await
+}
+
+pub async fn tournament(rpc: &RpcClient, tournament: &Pubkey) -> Result<TournamentAccount> {
+    account(rpc, tournament).await
+}
+
+pub async fn tournament_player(
+    rpc: &RpcClient,
+    tournament: &Pubkey,
+    player: &Pubkey,
+) -> Result<TournamentPlayerAccount> {
+    account(rpc, &pda::tournament_player(tournament, player)).await
+}
+
+/// The token program that owns `mint`: SPL Token or Token-2022. Native SOL
+/// tables pass SPL Token even though they move lamports.
+pub async fn token_program(rpc: &RpcClient, mint: &Pubkey) -> Result<Pubkey> {
+    if is_native(mint) {
+        return Ok(token::ID);
+    }
+    Ok(rpc.get_account(mint).await?.owner)
+}
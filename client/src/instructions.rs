@@ -0,0 +1,82 @@
+//! One builder per program handler. Each takes the handler's accounts as
+//! the program's own `accounts::*` struct, in the order Anchor expects, and
+//! its arguments as the program's types; see [`crate::pda`] for the
+//! addresses. Handlers that read `remaining_accounts`, like `eject_players`,
+//! need those pushed onto the returned instruction's `accounts`;
+//! [`crate::transactions`] does that for ejections.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use degods_poker::state::*;
+use degods_poker::{accounts, instruction, ID};
+
+macro_rules! instructions {
+    ($($name:ident($accounts:ident) => $data:ident { $($arg:ident: $ty:ty),* };)*) => {
+        $(
+            pub fn $name(accounts: accounts::$accounts, $($arg: $ty),*) -> Instruction {
+                Instruction {
+                    program_id: ID,
+                    accounts: accounts.to_account_metas(None),
+                    data: instruction::$data { $($arg),* }.data(),
+                }
+            }
+        )*
+    };
+}
+
+instructions! {
+    create_cash_game(CreateGameParams) => CreateCashGame { data: CreateGameData };
+    join_game(JoinGame) => JoinGame { data: JoinGameArgs };
+    join_waitlist(JoinWaitlistAccounts) => JoinWaitlist { data: JoinWaitlistArgs };
+    leave_waitlist(LeaveWaitlistAccounts) => LeaveWaitlist {};
+    remove_from_waitlist(RemoveFromWaitlistAccounts) => RemoveFromWaitlist {};
+    set_sitting_out(SitOutAccounts) => SetSittingOut { data: SitOutData };
+    add_chips(AddChips) => AddChips { data: AddChipsData };
+    eject_players(EjectPlayersAccounts) => EjectPlayers { data: EjectPlayersParams };
+    eject_sat_out_players(EjectSatOutPlayersAccounts) => EjectSatOutPlayers {};
+    dispute_settlement(DisputeSettlementAccounts) => DisputeSettlement { data: DisputeSettlementData };
+    release_settlement(PaySettlementAccounts) => ReleaseSettlement {};
+    resolve_dispute(PaySettlementAccounts) => ResolveDispute { data: ResolveDisputeData };
+    cash_out_signed(SignedCashOutAccounts) => CashOutSigned { data: BalanceStatement };
+    refund_player(RefundPlayerAccounts) => RefundPlayer { data: RefundPlayerParams };
+    leave_table(LeaveTableAccounts) => LeaveTable {};
+    withdraw_abandoned(LeaveTableAccounts) => WithdrawAbandoned {};
+    start_hand(StartHandAccounts) => StartHand {};
+    settle_hand(SettleHandAccounts) => SettleHand { data: SettleHandData };
+    commit_hand_history(SettleHandAccounts) => CommitHandHistory { data: CommitHandData };
+    open_shuffle(OpenShuffleAccounts) => OpenShuffle { data: ShuffleSeedData };
+    commit_shuffle_seed(ShuffleSeedAccounts) => CommitShuffleSeed { data: ShuffleSeedData };
+    reveal_shuffle_seed(ShuffleSeedAccounts) => RevealShuffleSeed { data: ShuffleSeedData };
+    deal_hand(DealHandAccounts) => DealHand { data: DealHandData };
+    player_action(HandActionAccounts) => PlayerAction { data: PlayerActionData };
+    reveal_board(RevealBoardAccounts) => RevealBoard { data: RevealBoardData };
+    reveal_hole_cards(HandActionAccounts) => RevealHoleCards { data: RevealHoleCardsData };
+    claim_hand_timeout(HandActionAccounts) => ClaimHandTimeout {};
+    settle_heads_up_hand(SettleHeadsUpHandAccounts) => SettleHeadsUpHand {};
+    withdraw_rake(WithdrawRakeAccounts) => WithdrawRake {};
+    set_game_transactor(SetGameTransactorAccounts) => SetGameTransactor { data: SetTransactorData };
+    set_dispute_settings(DisputeSettingsAccounts) => SetDisputeSettings { data: DisputeSettingsData };
+    set_signed_cash_outs(SignedCashOutSettingsAccounts) => SetSignedCashOuts { data: SignedCashOutSettingsData };
+    pause_game(GameStatusAccounts) => PauseGame {};
+    resume_game(GameStatusAccounts) => ResumeGame {};
+    begin_closing_game(GameStatusAccounts) => BeginClosingGame {};
+    close_game(CloseGame) => CloseGame {};
+    create_club(CreateClubAccounts) => CreateClub { name: String, settings: ClubSettingsData };
+    update_club_settings(ClubOwnerAccounts) => UpdateClubSettings { settings: ClubSettingsData };
+    set_club_admins(ClubOwnerAccounts) => SetClubAdmins { data: ClubMembersData };
+    set_club_allowlist(ClubAdminAccounts) => SetClubAllowlist { data: ClubMembersData };
+    grant_role(GrantRoleAccounts) => GrantRole { grantee: Pubkey, role: Role };
+    revoke_role(RevokeRoleAccounts) => RevokeRole {};
+    create_tournament(CreateTournamentParams) => CreateTournament { data: CreateTournamentData };
+    add_nft_tournament_prize(AddNFTTournamentPrizeParams) => AddNftTournamentPrize { data: NftTournamentPrizeData };
+    remove_nft_tournament_prize(RemoveNftTournamentPrizeParams) => RemoveNftTournamentPrize { data: NftTournamentPrizeData };
+    update_tournament_payouts(UpdateTournamentPayoutsParams) => UpdateTournamentPayouts { data: UpdateTournamentPayoutData };
+    flip_tournament_registration(FlipTournamentRegistrationParams) => FlipTournamentRegistration {};
+    register_tournament(JoinTournamentParams) => RegisterTournament {};
+    unregister_tournament(UnregisterTournamentParams) => UnregisterTournament {};
+    refund_tournament(RefundTournamentParams) => RefundTournament {};
+    start_tournament(StartTournamentParams) => StartTournament {};
+    payout_tournament_player(BustTournamentParams) => PayoutTournamentPlayer {};
+    close_tournament(CloseTournamentParams) => CloseTournament {};
+}
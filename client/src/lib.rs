@@ -0,0 +1,18 @@
+//! Rust client for the degods-poker program.
+//!
+//! - [`pda`] derives every program address the instructions take.
+//! - [`instructions`] has one typed builder per program handler, taking the
+//!   program's own `accounts::*` structs and argument types.
+//! - [`fetch`] reads and decodes program accounts over RPC.
+//! - [`transactions`] sends common multi-step calls, like cashing out several
+//!   players with `eject_players`.
+//! - [`Error`] turns failed transactions back into [`PokerError`]s.
+
+pub mod error;
+pub mod fetch;
+pub mod instructions;
+pub mod pda;
+pub mod transactions;
+
+pub use degods_poker::{accounts, state, PokerError, ID};
+pub use error::{poker_error, Error, Result};
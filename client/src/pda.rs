@@ -0,0 +1,95 @@
+//! Program addresses, mirroring the `seeds` on the program's accounts.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use degods_poker::vault::is_native;
+use degods_poker::ID;
+
+fn find(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &ID).0
+}
+
+/// Counts the games and tournaments `owner` has created.
+pub fn host_registry(owner: &Pubkey) -> Pubkey {
+    find(&[b"host", owner.as_ref()])
+}
+
+/// The `index`th game `owner` created, see `HostRegistry::games_created`.
+pub fn game(owner: &Pubkey, index: u64) -> Pubkey {
+    find(&[b"game", owner.as_ref(), &index.to_le_bytes()])
+}
+
+/// The `index`th tournament `owner` created, see
+/// `HostRegistry::tournaments_created`.
+pub fn tournament(owner: &Pubkey, index: u64) -> Pubkey {
+    find(&[b"tournament", owner.as_ref(), &index.to_le_bytes()])
+}
+
+/// A player's registration in a tournament.
+pub fn tournament_player(tournament: &Pubkey, player: &Pubkey) -> Pubkey {
+    find(&[tournament.as_ref(), player.as_ref()])
+}
+
+/// Escrow for the NFT paid to `place` in a tournament.
+pub fn nft_prize(tournament: &Pubkey, place: u16) -> Pubkey {
+    find(&[tournament.as_ref(), &place.to_le_bytes()])
+}
+
+/// Signs for a game's or tournament's vault; the handlers call it `pda_account`.
+/// On native SOL tables it holds the lamports itself.
+pub fn vault(scope: &Pubkey) -> Pubkey {
+    find(&[scope.as_ref()])
+}
+
+/// The vault's token account on token tables.
+pub fn vault_token_account(scope: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(&vault(scope), mint, token_program)
+}
+
+/// Where `wallet`'s payouts go: its associated token account, or the wallet
+/// itself on native SOL tables.
+pub fn payout_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    if is_native(mint) {
+        *wallet
+    } else {
+        get_associated_token_address_with_program_id(wallet, mint, token_program)
+    }
+}
+
+pub fn waitlist(game: &Pubkey, player: &Pubkey) -> Pubkey {
+    find(&[b"waitlist", game.as_ref(), player.as_ref()])
+}
+
+/// Where a waitlisted player's buy-in is escrowed on token tables.
+pub fn waitlist_escrow(
+    game: &Pubkey,
+    player: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    get_associated_token_address_with_program_id(&waitlist(game, player), mint, token_program)
+}
+
+pub fn club(owner: &Pubkey, name: &str) -> Pubkey {
+    find(&[b"club", owner.as_ref(), name.as_bytes()])
+}
+
+/// A role held on a game, tournament or club.
+pub fn role(scope: &Pubkey, grantee: &Pubkey) -> Pubkey {
+    find(&[b"role", scope.as_ref(), grantee.as_ref()])
+}
+
+pub fn shuffle(game: &Pubkey) -> Pubkey {
+    find(&[b"shuffle", game.as_ref()])
+}
+
+/// The heads-up hand being played at a game.
+pub fn hand(game: &Pubkey) -> Pubkey {
+    find(&[b"hand", game.as_ref()])
+}
+
+/// Signs the program's event self-CPIs; every handler that emits events
+/// takes it as `event_authority`.
+pub fn event_authority() -> Pubkey {
+    find(&[b"__event_authority"])
+}
@@ -0,0 +1,153 @@
+//! Calls that take more than one hand-built instruction.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use degods_poker::state::{EjectPlayersParams, GameAccount};
+use degods_poker::vault::is_native;
+use degods_poker::{accounts, PokerError, ID};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+use crate::{fetch, instructions, pda, Error, Result};
+
+/// One seat to cash out with `eject_players`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ejection {
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+/// Most seats a single `eject_players` instruction pays out, which keeps
+/// the transaction comfortably under the packet size limit.
+pub const EJECTIONS_PER_INSTRUCTION: usize = 10;
+
+/// Remaining accounts seating `players` from the front of the waitlist,
+/// which payouts that free a seat can pass, see `degods_poker::waitlist`.
+pub fn waitlist_accounts(
+    game: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    players: &[Pubkey],
+) -> Vec<AccountMeta> {
+    let mut metas = Vec::new();
+    for player in players {
+        metas.push(AccountMeta::new(pda::waitlist(game, player), false));
+        if !is_native(mint) {
+            metas.push(AccountMeta::new(
+                pda::waitlist_escrow(game, player, mint, token_program),
+                false,
+            ));
+        }
+        metas.push(AccountMeta::new(*player, false));
+    }
+    metas
+}
+
+/// `eject_players` instructions paying out `ejections` in batches, each
+/// with the players' payout accounts as its remaining accounts, followed by
+/// the waitlisted players taking the seats it frees. `operator` is the
+/// table's owner or transactor, or a dealer holding `role`.
+pub fn eject_players_instructions(
+    game: &Pubkey,
+    game_account: &GameAccount,
+    token_program: &Pubkey,
+    operator: &Pubkey,
+    role: Option<Pubkey>,
+    ejections: &[Ejection],
+) -> Result<Vec<Instruction>> {
+    let mint = game_account.token_mint;
+    let native = is_native(&mint);
+    let mut seated = game_account.players.len();
+    let mut waitlist = game_account.waitlist.iter().copied();
+    let mut batches = Vec::new();
+    for batch in ejections.chunks(EJECTIONS_PER_INSTRUCTION) {
+        let mut instruction = instructions::eject_players(
+            accounts::EjectPlayersAccounts {
+                game_account: *game,
+                token_account: (!native)
+                    .then(|| pda::vault_token_account(game, &mint, token_program)),
+                payer: *operator,
+                role,
+                pda_account: pda::vault(game),
+                mint: (!native).then_some(mint),
+                system_program: system_program::ID,
+                token_program: *token_program,
+                event_authority: pda::event_authority(),
+                program: ID,
+            },
+            EjectPlayersParams {
+                amounts: batch.iter().map(|e| e.amount).collect(),
+            },
+        );
+        for ejection in batch {
+            // the program fails the whole call on a player who isn't seated;
+            let seat = match game_account
+                .players
+                .iter()
+                .find(|p| p.address == ejection.player)
+            {
+                Some(seat) => seat,
+                None => return Err(Error::Program(PokerError::NotAtTable)),
+            };
+            // only a full payout frees the seat;
+            if ejection.amount >= seat.balance {
+                seated -= 1;
+            }
+            let payout = pda::payout_address(&ejection.player, &mint, token_program);
+            instruction.accounts.push(AccountMeta::new(payout, false));
+        }
+        let free = (game_account.max_players as usize).saturating_sub(seated);
+        let next: Vec<Pubkey> = waitlist.by_ref().take(free).collect();
+        seated += next.len();
+        instruction
+            .accounts
+            .extend(waitlist_accounts(game, &mint, token_program, &next));
+        batches.push(instruction);
+    }
+    Ok(batches)
+}
+
+/// Cashes out `ejections` at `game`, one transaction per batch, returning
+/// the signatures in order. Batches already sent stay paid out if a later
+/// one fails.
+pub async fn eject_players(
+    rpc: &RpcClient,
+    game: &Pubkey,
+    operator: &Keypair,
+    role: Option<Pubkey>,
+    ejections: &[Ejection],
+) -> Result<Vec<Signature>> {
+    let game_account = fetch::game(rpc, game).await?;
+    let token_program = fetch::token_program(rpc, &game_account.token_mint).await?;
+    let batches = eject_players_instructions(
+        game,
+        &game_account,
+        &token_program,
+        &operator.pubkey(),
+        role,
+        ejections,
+    )?;
+    let mut signatures = Vec::new();
+    for instruction in batches {
+        signatures.push(send(rpc, operator, &[instruction], &[]).await?);
+    }
+    Ok(signatures)
+}
+
+/// Sends `instructions` as one transaction paid for by `payer`, also signed
+/// by `signers`, and waits for it to confirm.
+pub async fn send(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<Signature> {
+    let blockhash = rpc.get_latest_blockhash().await?;
+    let mut keys = vec![payer];
+    keys.extend_from_slice(signers);
+    let transaction =
+        Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &keys, blockhash);
+    Ok(rpc.send_and_confirm_transaction(&transaction).await?)
+}
@@ -0,0 +1,86 @@
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+use degods_poker::accounts::GameStatusAccounts;
+use degods_poker_client::{instructions, pda, poker_error, PokerError, ID};
+
+#[test]
+fn error_codes_map_back_to_poker_errors() {
+    assert_eq!(
+        poker_error(ERROR_CODE_OFFSET).map(|e| e.name()),
+        Some("IncorrectTokenOwner".to_string())
+    );
+    let code = u32::from(PokerError::StaleStatement);
+    assert_eq!(
+        poker_error(code).map(|e| e.name()),
+        Some("StaleStatement".to_string())
+    );
+    let mut known = 0;
+    for code in ERROR_CODE_OFFSET..ERROR_CODE_OFFSET + 1000 {
+        if let Some(error) = poker_error(code) {
+            assert_eq!(u32::from(error), code);
+            known += 1;
+        }
+    }
+    assert!(poker_error(ERROR_CODE_OFFSET + known).is_none());
+    assert!(poker_error(ERROR_CODE_OFFSET - 1).is_none());
+}
+
+#[test]
+fn error_list_matches_program_enum() {
+    // the client's list is kept by hand, so check it against the program's source;
+    let source = include_str!("../../programs/degods-poker/src/errors.rs");
+    let body = &source[source.find("pub enum PokerError {").unwrap()..];
+    let declared: Vec<String> = body
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .take_while(|line| *line != "}")
+        .filter(|line| !line.starts_with('#') && !line.starts_with("//"))
+        .map(|line| line.trim_end_matches(',').to_string())
+        .collect();
+    let listed: Vec<String> = (0..)
+        .map_while(|i| poker_error(ERROR_CODE_OFFSET + i))
+        .map(|error| error.name())
+        .collect();
+    assert_eq!(listed, declared);
+}
+
+#[test]
+fn addresses_match_program_seeds() {
+    let owner = Pubkey::new_unique();
+    let (game, _) =
+        Pubkey::find_program_address(&[b"game", owner.as_ref(), &3u64.to_le_bytes()], &ID);
+    assert_eq!(pda::game(&owner, 3), game);
+    let (vault, _) = Pubkey::find_program_address(&[game.as_ref()], &ID);
+    assert_eq!(pda::vault(&game), vault);
+    assert_ne!(pda::shuffle(&game), pda::hand(&game));
+    let native = anchor_spl::token::spl_token::native_mint::ID;
+    assert_eq!(
+        pda::payout_address(&owner, &native, &anchor_spl::token::ID),
+        owner
+    );
+}
+
+#[test]
+fn builders_encode_the_handler() {
+    let game = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let instruction = instructions::pause_game(GameStatusAccounts {
+        game_account: game,
+        owner,
+        club: None,
+        role: None,
+        event_authority: pda::event_authority(),
+        program: ID,
+    });
+    assert_eq!(instruction.program_id, ID);
+    assert_eq!(
+        instruction.data,
+        hash(b"global:pause_game").to_bytes()[..8].to_vec()
+    );
+    let keys: Vec<Pubkey> = instruction.accounts.iter().map(|a| a.pubkey).collect();
+    // missing optional accounts are passed as the program id;
+    assert_eq!(keys, vec![game, owner, ID, ID, pda::event_authority(), ID]);
+    assert!(instruction.accounts[1].is_signer);
+}
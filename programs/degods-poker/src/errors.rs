@@ -40,4 +40,134 @@ pub enum PokerError {
     CantBustLastPlayerTournament,
     #[msg("NFTsEscrowedInTournament")]
     NFTsEscrowedInTournament,
+    #[msg("Seat balance too low")]
+    SeatBalanceTooLow,
+    #[msg("Vault balance does not cover seat balances")]
+    VaultUnderfunded,
+    #[msg("Rake must be at most 10000 basis points")]
+    InvalidRake,
+    #[msg("Hand deltas and rake must sum to zero")]
+    UnbalancedSettlement,
+    #[msg("Rake exceeds the table's rake limits")]
+    RakeTooLarge,
+    #[msg("Player appears more than once in settlement")]
+    DuplicatePlayer,
+    #[msg("Not Game Owner or Transactor")]
+    NotGameOperator,
+    #[msg("Hand in progress")]
+    HandInProgress,
+    #[msg("Inactivity timeout must be positive")]
+    InvalidInactivityTimeout,
+    #[msg("Game has not been abandoned")]
+    GameNotAbandoned,
+    #[msg("Invalid game status transition")]
+    InvalidGameStatus,
+    #[msg("Invalid blinds or buy-in limits")]
+    InvalidStakes,
+    #[msg("Table name too long")]
+    TableNameTooLong,
+    #[msg("Waitlist is full")]
+    WaitlistFull,
+    #[msg("Table has open seats")]
+    TableNotFull,
+    #[msg("Not next on the waitlist")]
+    NotNextOnWaitlist,
+    #[msg("Invalid seat")]
+    InvalidSeat,
+    #[msg("Seat taken")]
+    SeatTaken,
+    #[msg("Player has not been sat out long enough")]
+    SitOutLimitNotReached,
+    #[msg("Accounts don't match the table's currency")]
+    MintMismatch,
+    #[msg("Not a club admin")]
+    NotClubAdmin,
+    #[msg("Club name too long")]
+    ClubNameTooLong,
+    #[msg("Too many club admins")]
+    TooManyClubAdmins,
+    #[msg("Club allowlist is full")]
+    ClubAllowlistFull,
+    #[msg("Player is not on the club allowlist")]
+    NotOnAllowlist,
+    #[msg("Club account required")]
+    ClubRequired,
+    #[msg("Roles can only be held on a game, tournament or club")]
+    InvalidRoleScope,
+    #[msg("Only the owner can grant or revoke roles")]
+    NotScopeOwner,
+    #[msg("Shuffle round is not in the right phase")]
+    InvalidShufflePhase,
+    #[msg("Already committed a shuffle seed")]
+    AlreadyCommitted,
+    #[msg("Not every seated player has committed a shuffle seed")]
+    MissingShuffleCommitment,
+    #[msg("Not part of this shuffle round")]
+    NotShuffleParty,
+    #[msg("Revealed seed doesn't match the commitment")]
+    ShuffleSeedMismatch,
+    #[msg("Not available in this game mode")]
+    InvalidGameMode,
+    #[msg("Needs exactly two players ready to play")]
+    NeedTwoPlayers,
+    #[msg("Not your turn to act")]
+    NotYourTurn,
+    #[msg("Action not allowed here")]
+    InvalidAction,
+    #[msg("Bet or raise size not allowed")]
+    InvalidBet,
+    #[msg("Not expected at this point of the hand")]
+    InvalidStreet,
+    #[msg("Revealed card doesn't match its commitment")]
+    CardMismatch,
+    #[msg("Action clock hasn't run out")]
+    ActionClockRunning,
+    #[msg("Challenge window can't be negative")]
+    InvalidChallengeWindow,
+    #[msg("A challenge window needs an arbiter")]
+    InvalidArbiter,
+    #[msg("Too many payouts waiting on the challenge window")]
+    TooManyPendingSettlements,
+    #[msg("No pending payout for this player")]
+    NoPendingSettlement,
+    #[msg("Payout is already disputed")]
+    AlreadyDisputed,
+    #[msg("Challenge window has closed")]
+    ChallengeWindowClosed,
+    #[msg("Challenge window is still open")]
+    ChallengeWindowOpen,
+    #[msg("Payout is disputed and waits on the arbiter")]
+    PayoutDisputed,
+    #[msg("Payout isn't disputed")]
+    NotDisputed,
+    #[msg("Only the arbiter can resolve disputes")]
+    NotArbiter,
+    #[msg("Ruling pays more than was held back")]
+    InvalidSettlementAmount,
+    #[msg("Payouts are still waiting on the challenge window")]
+    SettlementsPending,
+    #[msg("Cash-outs need a balance statement signed by the player")]
+    SignedCashOutRequired,
+    #[msg("Balance statement isn't signed by both the player and the table")]
+    MissingStatementSignature,
+    #[msg("A newer balance statement has already been used")]
+    StaleStatement,
+    #[msg("Balance statement is for a different seat")]
+    SeatMismatch,
+    #[msg("Need exactly one amount per ejected player")]
+    EjectionAmountsMismatch,
+    #[msg("Amount out of range")]
+    AmountOverflow,
+    #[msg("Tables seat at most 10 players")]
+    TooManySeats,
+    #[msg("Every shuffle slot is taken")]
+    ShufflePartiesFull,
+    #[msg("The dealer never revealed its shuffle seed")]
+    DealerSeedMissing,
+    #[msg("Shuffle seeds can still be revealed")]
+    RevealWindowOpen,
+    #[msg("A waitlisted player's escrow or wallet account is missing")]
+    WaitlistAccountsMissing,
+    #[msg("Only a hand started from a shuffle round can be settled")]
+    NoHandInProgress,
 }
@@ -0,0 +1,282 @@
+//! Poker hand rankings, shared by the program and off-chain dealers.
+//!
+//! Cards use the deck encoding from `shuffle`: card `c` is rank `c / 4` and
+//! suit `c % 4`, ranks counted up from the deck's lowest card (the two, or the
+//! six in short deck). Nothing here allocates; a Hold'em hand is ranked in a
+//! single pass over its cards, and Omaha ranks each of its at most 100 legal
+//! five-card combinations. Every function returns `None` for a hand that
+//! can't exist: the wrong number of cards, a card outside the deck or the
+//! same card twice.
+
+use crate::state::GameVariant;
+
+/// Hand categories, weakest first under standard rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+/// Which deck a hand is dealt from and how it ranks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rules {
+    Standard,
+    /// Six through ace. A flush beats a full house and A-6-7-8-9 is the
+    /// lowest straight.
+    ShortDeck,
+}
+
+impl Rules {
+    pub fn for_variant(variant: GameVariant) -> Self {
+        match variant {
+            GameVariant::ShortDeck => Rules::ShortDeck,
+            _ => Rules::Standard,
+        }
+    }
+
+    /// Cards in the deck.
+    pub fn deck_size(self) -> u8 {
+        match self {
+            Rules::Standard => 52,
+            Rules::ShortDeck => 36,
+        }
+    }
+
+    /// Rank on the standard scale, two = 0 through ace = 12.
+    fn rank(self, card: u8) -> Option<u8> {
+        if card >= self.deck_size() {
+            return None;
+        }
+        match self {
+            Rules::Standard => Some(card / 4),
+            Rules::ShortDeck => Some(card / 4 + 4),
+        }
+    }
+
+    fn ordinal(self, category: Category) -> u32 {
+        match (self, category) {
+            (Rules::ShortDeck, Category::Flush) => Category::FullHouse as u32,
+            (Rules::ShortDeck, Category::FullHouse) => Category::Flush as u32,
+            _ => category as u32,
+        }
+    }
+
+    /// Top card of the best straight among `ranks`, if there is one.
+    fn straight_high(self, ranks: u16) -> Option<u8> {
+        for high in (4..13).rev() {
+            let run = 0b11111 << (high - 4);
+            if ranks & run == run {
+                return Some(high);
+            }
+        }
+        // the ace plays low under the deck's four lowest ranks;
+        let wheel_high = match self {
+            Rules::Standard => 3,
+            Rules::ShortDeck => 7,
+        };
+        let wheel = 1 << 12 | 0b1111 << (wheel_high - 3);
+        if ranks & wheel == wheel {
+            Some(wheel_high)
+        } else {
+            None
+        }
+    }
+}
+
+/// How strong a hand is. Higher ranks win and equal ranks split the pot;
+/// ranks are only comparable under the same `Rules`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandRank {
+    strength: u32,
+    category: Category,
+}
+
+impl HandRank {
+    /// `ranks` are the standard-scale ranks that break ties within the
+    /// category, most significant first.
+    fn new(rules: Rules, category: Category, ranks: &[u8]) -> Self {
+        let mut strength = rules.ordinal(category) << 20;
+        for (i, rank) in ranks.iter().enumerate() {
+            strength |= (*rank as u32) << (16 - 4 * i);
+        }
+        HandRank { strength, category }
+    }
+
+    pub fn category(&self) -> Category {
+        self.category
+    }
+
+    /// The category's place under the hand's rules in bits 20 and up, then
+    /// up to five four-bit ranks.
+    pub fn strength(&self) -> u32 {
+        self.strength
+    }
+}
+
+/// Best five-card hand from 5 to 7 cards, any of which may play, as in
+/// Hold'em.
+pub fn best_hand(cards: &[u8], rules: Rules) -> Option<HandRank> {
+    if !(5..=7).contains(&cards.len()) {
+        return None;
+    }
+    let mut seen = 0u64;
+    let mut counts = [0u8; 13];
+    let mut suits = [0u16; 4];
+    for card in cards {
+        let rank = rules.rank(*card)?;
+        if seen & 1 << card != 0 {
+            return None;
+        }
+        seen |= 1 << card;
+        counts[rank as usize] += 1;
+        suits[(card % 4) as usize] |= 1 << rank;
+    }
+    // ranks held at least once, exactly twice, and three or more times;
+    let (mut ranks, mut pairs, mut trips, mut quads) = (0u16, 0u16, 0u16, 0u16);
+    for (rank, count) in counts.iter().enumerate() {
+        let bit = 1 << rank;
+        match count {
+            0 => continue,
+            1 => {}
+            2 => pairs |= bit,
+            3 => trips |= bit,
+            _ => quads |= bit,
+        }
+        ranks |= bit;
+    }
+    // with seven cards or fewer only one suit can make a flush;
+    let suited = suits.iter().copied().find(|s| s.count_ones() >= 5);
+
+    if let Some(high) = suited.and_then(|s| rules.straight_high(s)) {
+        return Some(HandRank::new(rules, Category::StraightFlush, &[high]));
+    }
+    if quads != 0 {
+        let quad = highest(quads);
+        let kicker = highest(ranks & !(1 << quad));
+        return Some(HandRank::new(rules, Category::FourOfAKind, &[quad, kicker]));
+    }
+    let full_house = if trips != 0 {
+        let trip = highest(trips);
+        let rest = (trips | pairs) & !(1 << trip);
+        (rest != 0).then(|| HandRank::new(rules, Category::FullHouse, &[trip, highest(rest)]))
+    } else {
+        None
+    };
+    let flush = suited.map(|s| {
+        let (top, _) = top_ranks(s, 5);
+        HandRank::new(rules, Category::Flush, &top)
+    });
+    // the rules decide which of these two outranks the other;
+    if let Some(rank) = full_house.max(flush) {
+        return Some(rank);
+    }
+    if let Some(high) = rules.straight_high(ranks) {
+        return Some(HandRank::new(rules, Category::Straight, &[high]));
+    }
+    if trips != 0 {
+        let trip = highest(trips);
+        let (kickers, _) = top_ranks(ranks & !(1 << trip), 2);
+        return Some(HandRank::new(
+            rules,
+            Category::ThreeOfAKind,
+            &[trip, kickers[0], kickers[1]],
+        ));
+    }
+    match pairs.count_ones() {
+        0 => {
+            let (top, _) = top_ranks(ranks, 5);
+            Some(HandRank::new(rules, Category::HighCard, &top))
+        }
+        1 => {
+            let pair = highest(pairs);
+            let (kickers, _) = top_ranks(ranks & !pairs, 3);
+            Some(HandRank::new(
+                rules,
+                Category::Pair,
+                &[pair, kickers[0], kickers[1], kickers[2]],
+            ))
+        }
+        _ => {
+            let (top, rest) = top_ranks(pairs, 2);
+            // a third pair can still play as the kicker;
+            let kicker = highest(ranks & !(pairs & !rest));
+            Some(HandRank::new(
+                rules,
+                Category::TwoPair,
+                &[top[0], top[1], kicker],
+            ))
+        }
+    }
+}
+
+/// Best hand using exactly two of the 4 or 5 `hole` cards and three of the
+/// 3 to 5 `board` cards, as in Omaha.
+pub fn best_omaha_hand(hole: &[u8], board: &[u8]) -> Option<HandRank> {
+    if !(4..=5).contains(&hole.len()) || !(3..=5).contains(&board.len()) {
+        return None;
+    }
+    let mut seen = 0u64;
+    for card in hole.iter().chain(board) {
+        if *card >= 52 || seen & 1 << card != 0 {
+            return None;
+        }
+        seen |= 1 << card;
+    }
+    let mut best = None;
+    for i in 0..hole.len() {
+        for j in i + 1..hole.len() {
+            for a in 0..board.len() {
+                for b in a + 1..board.len() {
+                    for c in b + 1..board.len() {
+                        let five = [hole[i], hole[j], board[a], board[b], board[c]];
+                        best = best.max(best_hand(&five, Rules::Standard));
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+/// What a player's `hole` cards make with the `board` at a table of
+/// `variant`.
+pub fn showdown(variant: GameVariant, hole: &[u8], board: &[u8]) -> Option<HandRank> {
+    match variant {
+        GameVariant::Nlhe | GameVariant::ShortDeck => {
+            if hole.len() != 2 || board.len() > 5 {
+                return None;
+            }
+            let mut cards = [0u8; 7];
+            cards[..2].copy_from_slice(hole);
+            cards[2..2 + board.len()].copy_from_slice(board);
+            best_hand(&cards[..2 + board.len()], Rules::for_variant(variant))
+        }
+        GameVariant::Plo if hole.len() == 4 => best_omaha_hand(hole, board),
+        GameVariant::Plo5 if hole.len() == 5 => best_omaha_hand(hole, board),
+        _ => None,
+    }
+}
+
+fn highest(mask: u16) -> u8 {
+    15 - mask.leading_zeros() as u8
+}
+
+/// The `n` highest ranks in `mask`, highest first, and the ranks left over.
+fn top_ranks(mut mask: u16, n: usize) -> ([u8; 5], u16) {
+    let mut top = [0u8; 5];
+    for slot in top.iter_mut().take(n) {
+        if mask == 0 {
+            break;
+        }
+        *slot = highest(mask);
+        mask &= !(1 << *slot);
+    }
+    (top, mask)
+}
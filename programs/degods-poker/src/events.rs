@@ -0,0 +1,255 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{GameStatus, HandAction, SeatDelta};
+
+// Emitted through a self-CPI (`emit_cpi!`) so they land in the transaction's
+// inner instructions and survive log truncation. Amounts are what actually
+// moved, after any transfer fees.
+
+// Cash Games
+
+#[event]
+pub struct GameCreated {
+    pub game: Pubkey,
+    pub owner: Pubkey,
+    pub club: Pubkey,
+    pub token_mint: Pubkey,
+    pub max_players: u16,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub min_deposit: u64,
+    pub max_deposit: u64,
+}
+
+/// Also emitted when a player is seated from the waitlist.
+#[event]
+pub struct PlayerJoined {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub seat: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ChipsAdded {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+/// `amounts[i]` is what was paid out to `players[i]`. With a challenge
+/// window the payouts are held until `release_at`, zero when they were paid
+/// straight away.
+#[event]
+pub struct PlayersEjected {
+    pub game: Pubkey,
+    pub players: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub release_at: i64,
+}
+
+/// `claimed` is what the player says they're owed, `evidence` the hash of
+/// their signed record of the hand. Without a ruling by `rule_by` the payout
+/// is released as reported.
+#[event]
+pub struct SettlementDisputed {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub claimed: u64,
+    pub evidence: [u8; 32],
+    pub rule_by: i64,
+}
+
+/// A held payout left the vault, `disputed` when the arbiter ruled on it.
+/// `returned` is what was held but not paid, credited back to the player's
+/// seat or, once they've left, held for them as an undisputed payout.
+#[event]
+pub struct SettlementPaid {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub disputed: bool,
+    pub returned: u64,
+}
+
+#[event]
+pub struct PlayerRefunded {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+/// A player cashed out their whole seat, `abandoned` when the table had gone quiet.
+#[event]
+pub struct PlayerLeft {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub abandoned: bool,
+}
+
+/// A player cashed out on a signed balance statement; `release_at` is zero
+/// when paid straight away, otherwise the payout is held until then.
+#[event]
+pub struct SignedCashOut {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub seat: u8,
+    pub nonce: u64,
+    pub balance: u64,
+    pub release_at: i64,
+}
+
+#[event]
+pub struct HandSettled {
+    pub game: Pubkey,
+    pub deltas: Vec<SeatDelta>,
+    pub rake: u64,
+    pub hand_history: [u8; 32],
+}
+
+#[event]
+pub struct HandCommitted {
+    pub game: Pubkey,
+    pub hand_hash: [u8; 32],
+    pub hand_history: [u8; 32],
+    pub hands_committed: u64,
+}
+
+#[event]
+pub struct ShuffleOpened {
+    pub game: Pubkey,
+    pub hand: u64,
+    pub dealer_commitment: [u8; 32],
+    /// Seated players sat out for never revealing the last round's seed.
+    pub sat_out: Vec<Pubkey>,
+}
+
+/// Every party has revealed; `deck_seed` feeds `shuffle::permutation`.
+#[event]
+pub struct ShuffleRevealed {
+    pub game: Pubkey,
+    pub hand: u64,
+    pub deck_seed: [u8; 32],
+}
+
+#[event]
+pub struct HandDealt {
+    pub game: Pubkey,
+    pub hand: u64,
+    /// Button first.
+    pub players: [Pubkey; 2],
+    pub card_commitments: [[u8; 32]; 9],
+}
+
+#[event]
+pub struct PlayerActed {
+    pub game: Pubkey,
+    pub hand: u64,
+    pub player: Pubkey,
+    pub action: HandAction,
+    pub pot: u64,
+}
+
+#[event]
+pub struct BoardRevealed {
+    pub game: Pubkey,
+    pub hand: u64,
+    pub board: Vec<u8>,
+}
+
+/// `payouts` are what each player got back out of the pot, `hole_cards`
+/// only count where `hole_cards_revealed` is set.
+#[event]
+pub struct HandFinished {
+    pub game: Pubkey,
+    pub hand: u64,
+    pub players: [Pubkey; 2],
+    pub payouts: [u64; 2],
+    pub rake: u64,
+    pub board: Vec<u8>,
+    pub hole_cards: [[u8; 2]; 2],
+    pub hole_cards_revealed: [bool; 2],
+    /// Called off: the dealer stalled, the cards didn't match the deck or the
+    /// seeds to check them never came in.
+    pub voided: bool,
+}
+
+#[event]
+pub struct RakeWithdrawn {
+    pub game: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct GameStatusChanged {
+    pub game: Pubkey,
+    pub status: GameStatus,
+}
+
+#[event]
+pub struct GameClosed {
+    pub game: Pubkey,
+    pub rake_paid: u64,
+    pub dust: u64,
+}
+
+// Tournaments
+
+#[event]
+pub struct TournamentCreated {
+    pub tournament: Pubkey,
+    pub owner: Pubkey,
+    pub club: Pubkey,
+    pub token_mint: Pubkey,
+    pub max_players: u16,
+    pub entry_cost: u64,
+    pub entry_fee: u64,
+    pub guarantee: u64,
+}
+
+#[event]
+pub struct TournamentRegistrationChanged {
+    pub tournament: Pubkey,
+    pub registration_open: bool,
+}
+
+#[event]
+pub struct PlayerRegistered {
+    pub tournament: Pubkey,
+    pub player: Pubkey,
+    pub paid: u64,
+}
+
+/// The player left before the start, or the host refunded them.
+#[event]
+pub struct PlayerUnregistered {
+    pub tournament: Pubkey,
+    pub player: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct TournamentStarted {
+    pub tournament: Pubkey,
+    pub players: u16,
+}
+
+/// `position` is where the player finished, `payout` is zero outside the money.
+#[event]
+pub struct PlayerBusted {
+    pub tournament: Pubkey,
+    pub player: Pubkey,
+    pub position: u16,
+    pub payout: u64,
+}
+
+#[event]
+pub struct TournamentClosed {
+    pub tournament: Pubkey,
+    pub remainder: u64,
+}
@@ -0,0 +1,353 @@
+//! Heads-up No-Limit Hold'em played out on a `HandAccount`.
+//!
+//! Cards come from the table's commit-reveal round (see `shuffle`). The
+//! dealer opens the round, both players commit and then hand their seeds to
+//! the dealer privately, so neither side can steer the deck alone. The dealer
+//! commits to the nine cards the hand can use with `sha256([card] || salt)`
+//! and seals each player's hole cards to them. Board cards are opened against
+//! those commitments street by street and hole cards at showdown. The pot is
+//! only paid once the seeds are revealed publicly and every card shown is the
+//! one `shuffle::permutation` put in its place.
+//!
+//! The dealer holds every seed before the hand is dealt, so it knows both
+//! players' hole cards and the board for the whole hand. Players have to trust
+//! it not to share them. All the program can catch is a tampered deck: if a
+//! card shown isn't the one the seeds put in its place, the hand is void and
+//! both players get back what they put in.
+
+use std::cmp::Ordering;
+
+use anchor_lang::prelude::*;
+
+use crate::errors::PokerError;
+use crate::evaluator::showdown;
+use crate::shuffle;
+use crate::state::{
+    GameAccount, GameVariant, HandAccount, HandAction, RevealedCard, ShuffleAccount, Street,
+    BOARD_POSITION, HOLE_CARD_POSITIONS,
+};
+
+/// How a finished hand splits the pot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// One player takes the pot, by fold, timeout or showdown.
+    Winner(u8),
+    Split,
+    /// The hand can't be finished; everyone gets back what they put in.
+    Void,
+}
+
+impl HandAccount {
+    /// Starts a new hand between `players` (button first) and posts the blinds.
+    /// `stacks` are their seat balances.
+    pub fn deal(
+        &mut self,
+        players: [Pubkey; 2],
+        stacks: [u64; 2],
+        small_blind: u64,
+        big_blind: u64,
+        deadline: i64,
+    ) {
+        self.hand += 1;
+        self.players = players;
+        self.stacks = stacks;
+        self.committed = [0; 2];
+        self.street_bets = [0; 2];
+        self.acted = [false; 2];
+        self.street = Street::Preflop;
+        self.betting_closed = false;
+        self.last_raise = big_blind;
+        self.big_blind = big_blind;
+        self.board = Vec::new();
+        self.hole_cards = [[0; 2]; 2];
+        self.hole_cards_revealed = [false; 2];
+        self.outcome = None;
+        self.put_in(0, small_blind);
+        self.put_in(1, big_blind);
+        // the button acts first preflop;
+        self.open_action(0, deadline);
+    }
+
+    /// Chips in the middle, including anything not yet called.
+    pub fn pot(&self) -> u64 {
+        self.committed[0] + self.committed[1]
+    }
+
+    pub fn act(
+        &mut self,
+        player: usize,
+        action: HandAction,
+        deadline: i64,
+    ) -> Result<Option<Outcome>> {
+        if self.betting_closed || self.street == Street::Complete {
+            return Err(PokerError::InvalidStreet.into());
+        }
+        if player != self.to_act as usize {
+            return Err(PokerError::NotYourTurn.into());
+        }
+        let opponent = 1 - player;
+        let to_call = self.street_bets[opponent].saturating_sub(self.street_bets[player]);
+        match action {
+            HandAction::Fold => return Ok(Some(Outcome::Winner(opponent as u8))),
+            HandAction::Check => {
+                if to_call > 0 {
+                    return Err(PokerError::InvalidAction.into());
+                }
+            }
+            HandAction::Call => {
+                if to_call == 0 {
+                    return Err(PokerError::InvalidAction.into());
+                }
+                self.put_in(player, to_call);
+            }
+            HandAction::RaiseTo(total) => {
+                let all_in = self.street_bets[player] + self.stacks[player];
+                // nobody left to raise against once the opponent is all in;
+                if total <= self.street_bets[opponent]
+                    || total > all_in
+                    || self.stacks[opponent] == 0
+                {
+                    return Err(PokerError::InvalidBet.into());
+                }
+                let raise = total - self.street_bets[opponent];
+                // a short all-in is allowed but doesn't change the minimum raise;
+                if raise < self.last_raise && total != all_in {
+                    return Err(PokerError::InvalidBet.into());
+                }
+                self.last_raise = self.last_raise.max(raise);
+                self.put_in(player, total - self.street_bets[player]);
+                self.acted[opponent] = false;
+            }
+        }
+        self.acted[player] = true;
+        self.open_action(opponent, deadline);
+        Ok(None)
+    }
+
+    /// Opens the next street's cards and its betting round.
+    pub fn reveal_board(&mut self, cards: &[RevealedCard], deadline: i64) -> Result<()> {
+        let (count, next) = match self.street {
+            Street::Preflop => (3, Street::Flop),
+            Street::Flop => (1, Street::Turn),
+            Street::Turn => (1, Street::River),
+            _ => return Err(PokerError::InvalidStreet.into()),
+        };
+        if !self.betting_closed || cards.len() != count {
+            return Err(PokerError::InvalidStreet.into());
+        }
+        for card in cards {
+            self.check_card(BOARD_POSITION + self.board.len(), card)?;
+            self.board.push(card.card);
+        }
+        self.street = next;
+        self.betting_closed = false;
+        // the big blind acts first after the flop;
+        self.open_action(1, deadline);
+        Ok(())
+    }
+
+    /// Opens a player's hole cards at showdown; the hand is decided once both are in.
+    pub fn reveal_hole_cards(
+        &mut self,
+        player: usize,
+        cards: &[RevealedCard; 2],
+    ) -> Result<Option<Outcome>> {
+        if self.street != Street::Showdown || self.hole_cards_revealed[player] {
+            return Err(PokerError::InvalidStreet.into());
+        }
+        if cards[0].card == cards[1].card {
+            return Err(PokerError::CardMismatch.into());
+        }
+        for (i, card) in cards.iter().enumerate() {
+            self.check_card(HOLE_CARD_POSITIONS[player] + i, card)?;
+        }
+        self.hole_cards[player] = [cards[0].card, cards[1].card];
+        self.hole_cards_revealed[player] = true;
+        if !self.hole_cards_revealed[1 - player] {
+            return Ok(None);
+        }
+        let rank = |p: usize| showdown(GameVariant::Nlhe, &self.hole_cards[p], &self.board);
+        let ranks = match (rank(0), rank(1)) {
+            (Some(button), Some(big_blind)) => [button, big_blind],
+            _ => return Err(PokerError::CardMismatch.into()),
+        };
+        Ok(Some(match ranks[0].cmp(&ranks[1]) {
+            Ordering::Greater => Outcome::Winner(0),
+            Ordering::Less => Outcome::Winner(1),
+            Ordering::Equal => Outcome::Split,
+        }))
+    }
+
+    /// Whoever the hand is waiting on has let the clock run out. A player
+    /// checks if they can and folds otherwise; a player who won't show at
+    /// showdown gives up the pot. A hand stalled by the dealer is void.
+    pub fn time_out(&mut self, deadline: i64) -> Result<Option<Outcome>> {
+        if self.street == Street::Showdown {
+            return Ok(Some(match self.hole_cards_revealed {
+                [true, false] => Outcome::Winner(0),
+                [false, true] => Outcome::Winner(1),
+                _ => Outcome::Void,
+            }));
+        }
+        if self.betting_closed {
+            return Ok(Some(Outcome::Void));
+        }
+        let player = self.to_act as usize;
+        let action = if self.street_bets[player] >= self.street_bets[1 - player] {
+            HandAction::Check
+        } else {
+            HandAction::Fold
+        };
+        self.act(player, action, deadline)
+    }
+
+    /// Ends the betting on `outcome`, which stands once the round's seeds are
+    /// public and the cards check out. The parties have until `deadline` to
+    /// reveal.
+    pub fn conclude(&mut self, outcome: Outcome, deadline: i64) {
+        self.outcome = Some(outcome);
+        self.street = Street::Complete;
+        self.action_deadline = deadline;
+    }
+
+    /// Whether every card shown is the one `deck` has at its commitment's
+    /// position.
+    pub fn dealt_from(&self, deck: &[u8]) -> bool {
+        let hole_cards = (0..2).filter(|&p| self.hole_cards_revealed[p]).all(|p| {
+            self.hole_cards[p]
+                .iter()
+                .enumerate()
+                .all(|(i, card)| deck.get(HOLE_CARD_POSITIONS[p] + i) == Some(card))
+        });
+        hole_cards
+            && self
+                .board
+                .iter()
+                .enumerate()
+                .all(|(i, card)| deck.get(BOARD_POSITION + i) == Some(card))
+    }
+
+    /// The result when the reveal clock runs out with seeds still missing. A
+    /// player who kept theirs loses the pot; otherwise the deck can't be
+    /// checked and the hand is void.
+    pub fn forfeit(&self, shuffle: &ShuffleAccount) -> Outcome {
+        // the dealer's seed is always first;
+        if !matches!(shuffle.parties.first(), Some(dealer) if dealer.revealed) {
+            return Outcome::Void;
+        }
+        let revealed = |party: &Pubkey| {
+            shuffle
+                .parties
+                .iter()
+                .any(|c| c.party == *party && c.revealed)
+        };
+        match [revealed(&self.players[0]), revealed(&self.players[1])] {
+            [true, false] => Outcome::Winner(0),
+            [false, true] => Outcome::Winner(1),
+            _ => Outcome::Void,
+        }
+    }
+
+    /// What each player gets back and the rake taken. Uncalled chips go back
+    /// to whoever bet them, and there's no rake unless the flop was dealt.
+    pub fn payouts(&self, outcome: Outcome, game: &GameAccount) -> ([u64; 2], u64) {
+        if outcome == Outcome::Void {
+            return (self.committed, 0);
+        }
+        let matched = self.committed[0].min(self.committed[1]);
+        let mut payouts = [self.committed[0] - matched, self.committed[1] - matched];
+        let pot = matched * 2;
+        let rake = if self.board.is_empty() {
+            0
+        } else {
+            game.max_rake(pot)
+        };
+        let prize = pot - rake;
+        match outcome {
+            Outcome::Winner(player) => payouts[player as usize] += prize,
+            // the odd chip goes to the big blind;
+            _ => {
+                payouts[0] += prize / 2;
+                payouts[1] += prize - prize / 2;
+            }
+        }
+        (payouts, rake)
+    }
+
+    fn put_in(&mut self, player: usize, amount: u64) {
+        let amount = amount.min(self.stacks[player]);
+        self.stacks[player] -= amount;
+        self.street_bets[player] += amount;
+        self.committed[player] += amount;
+    }
+
+    fn needs_action(&self, player: usize) -> bool {
+        let opponent = 1 - player;
+        self.stacks[player] > 0
+            && (self.street_bets[player] < self.street_bets[opponent]
+                || (!self.acted[player] && self.stacks[opponent] > 0))
+    }
+
+    /// Hands the action to `first`, or the other player, or ends the street
+    /// when neither has anything left to do.
+    fn open_action(&mut self, first: usize, deadline: i64) {
+        self.action_deadline = deadline;
+        if self.needs_action(first) {
+            self.to_act = first as u8;
+        } else if self.needs_action(1 - first) {
+            self.to_act = (1 - first) as u8;
+        } else {
+            self.street_bets = [0; 2];
+            self.acted = [false; 2];
+            self.last_raise = self.big_blind;
+            self.betting_closed = true;
+            if self.street == Street::River {
+                self.street = Street::Showdown;
+            }
+        }
+    }
+
+    fn check_card(&self, position: usize, card: &RevealedCard) -> Result<()> {
+        let seen = self.board.contains(&card.card)
+            || (0..2)
+                .any(|p| self.hole_cards_revealed[p] && self.hole_cards[p].contains(&card.card));
+        if card.card >= 52
+            || seen
+            || shuffle::card_commitment(card.card, &card.salt) != self.card_commitments[position]
+        {
+            return Err(PokerError::CardMismatch.into());
+        }
+        Ok(())
+    }
+}
+
+/// Credits the hand's result to the seats and frees the table for the next one.
+pub fn finish_hand(
+    game: &mut GameAccount,
+    hand: &mut HandAccount,
+    outcome: Outcome,
+) -> Result<([u64; 2], u64)> {
+    let (payouts, rake) = hand.payouts(outcome, game);
+    for (i, player) in hand.players.iter().enumerate() {
+        let seat = match game.players.iter_mut().find(|p| p.address == *player) {
+            Some(seat) => seat,
+            None => return Err(PokerError::NotAtTable.into()),
+        };
+        let balance = match seat.balance.checked_add(payouts[i]) {
+            Some(balance) => balance,
+            None => return Err(PokerError::AmountOverflow.into()),
+        };
+        seat.balance = match balance.checked_sub(hand.committed[i]) {
+            Some(balance) => balance,
+            None => return Err(PokerError::SeatBalanceTooLow.into()),
+        };
+    }
+    game.rake_accrued = match game.rake_accrued.checked_add(rake) {
+        Some(rake_accrued) => rake_accrued,
+        None => return Err(PokerError::AmountOverflow.into()),
+    };
+    game.hand_in_progress = false;
+    hand.street = Street::Complete;
+    hand.outcome = None;
+    Ok((payouts, rake))
+}
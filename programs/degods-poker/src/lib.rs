@@ -1,19 +1,27 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{transfer, Transfer};
 pub mod errors;
 pub use errors::PokerError;
+pub mod evaluator;
+pub mod events;
+pub use events::*;
+pub mod holdem;
+use holdem::{finish_hand, Outcome};
 pub mod state;
 pub use state::*;
+pub mod shuffle;
+pub mod statement;
+pub mod vault;
+pub mod waitlist;
+use vault::{
+    check_mint, close_vault, deposit_tokens, fund_vault, holder, is_native, payout_address,
+    transfer_tokens, vault_balance,
+};
+use waitlist::{refund_escrow, seat_waitlisted, split_remaining};
 declare_id!("DyQWFkDFMrTm4rcLKC1ayM5fKpYdW3DLpybBPwvdWZS8");
 
 #[program]
 pub mod degods_poker_program {
 
-    use anchor_spl::{
-        associated_token::get_associated_token_address,
-        token::{close_account, CloseAccount},
-    };
-
     use super::*;
 
     pub fn create_cash_game(ctx: Context<CreateGameParams>, data: CreateGameData) -> Result<()> {
@@ -22,23 +30,146 @@ pub mod degods_poker_program {
             "Creating game account with max players: {:?}",
             data.max_players
         );
+        if data.inactivity_timeout <= 0 {
+            return Err(PokerError::InvalidInactivityTimeout.into());
+        }
+        if data.small_blind > data.big_blind {
+            return Err(PokerError::InvalidStakes.into());
+        }
+        if data.table_name.len() > MAX_TABLE_NAME_LEN {
+            return Err(PokerError::TableNameTooLong.into());
+        }
+        // the game account and the shuffle round only have room for MAX_SEATS;
+        if data.max_players as usize > MAX_SEATS {
+            return Err(PokerError::TooManySeats.into());
+        }
+        let mut min_deposit = data.min_deposit;
+        let mut max_deposit = data.max_deposit;
+        if data.min_buy_in_bb > 0 {
+            min_deposit = match data.big_blind.checked_mul(data.min_buy_in_bb as u64) {
+                Some(deposit) => deposit,
+                None => return Err(PokerError::InvalidStakes.into()),
+            };
+        }
+        if data.max_buy_in_bb > 0 {
+            max_deposit = match data.big_blind.checked_mul(data.max_buy_in_bb as u64) {
+                Some(deposit) => deposit,
+                None => return Err(PokerError::InvalidStakes.into()),
+            };
+        }
+        if min_deposit > max_deposit {
+            return Err(PokerError::InvalidStakes.into());
+        }
+        // the on-chain engine only deals heads-up No-Limit Hold'em;
+        if let GameMode::HeadsUp { action_timeout } = data.mode {
+            if data.max_players != 2 || data.variant != GameVariant::Nlhe || action_timeout <= 0 {
+                return Err(PokerError::InvalidGameMode.into());
+            }
+        }
+        let club = &ctx.accounts.club;
+        let mut rake_bps = data.rake_bps;
+        let mut rake_cap = data.rake_cap;
+        let mut rake_recipient = data.rake_recipient;
+        if let Some(club) = club {
+            // club defaults fill in whatever the host left unset;
+            if rake_bps == 0 && rake_cap == 0 {
+                rake_bps = club.default_rake_bps;
+                rake_cap = club.default_rake_cap;
+            }
+            if rake_recipient == Pubkey::default() {
+                rake_recipient = club.default_rake_recipient;
+            }
+            game_account.club = club.key();
+        }
+        // rake has to go somewhere for withdraw_rake and close_game to work;
+        if rake_recipient == Pubkey::default() {
+            rake_recipient = ctx.accounts.payer.key();
+        }
+        if rake_bps > 10_000 {
+            return Err(PokerError::InvalidRake.into());
+        }
         game_account.max_players = data.max_players;
-        game_account.min_deposit = data.min_deposit;
-        game_account.max_deposit = data.max_deposit;
+        game_account.min_deposit = min_deposit;
+        game_account.max_deposit = max_deposit;
+        game_account.small_blind = data.small_blind;
+        game_account.big_blind = data.big_blind;
+        game_account.ante = data.ante;
+        game_account.variant = data.variant;
+        game_account.table_name = data.table_name;
+        game_account.min_buy_in_bb = data.min_buy_in_bb;
+        game_account.max_buy_in_bb = data.max_buy_in_bb;
+        game_account.waitlist = Vec::new();
+        game_account.max_sit_out_seconds = data.max_sit_out_seconds;
+        game_account.mode = data.mode;
         game_account.owner = *ctx.accounts.payer.key;
-        game_account.token_mint = data.token_mint;
+        game_account.transactor = ctx.accounts.transactor.key();
+        game_account.token_mint = resolve_token_mint(club, data.token_mint);
         game_account.players = Box::new(Vec::<SeatedPlayer>::with_capacity(
             data.max_players as usize,
         ));
+        game_account.rake_bps = rake_bps;
+        game_account.rake_cap = rake_cap;
+        game_account.rake_recipient = rake_recipient;
+        game_account.rake_accrued = 0;
+        game_account.hand_in_progress = false;
+        game_account.inactivity_timeout = data.inactivity_timeout;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        game_account.status = GameStatus::Open;
+        game_account.hand_history = [0; 32];
+        game_account.hands_committed = 0;
+        let host_registry = &mut ctx.accounts.host_registry;
+        host_registry.owner = ctx.accounts.payer.key();
+        host_registry.games_created += 1;
+        if is_native(&game_account.token_mint) {
+            fund_vault(
+                &ctx.accounts.system_program,
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.pda_account.to_account_info(),
+            )?;
+        }
+        emit_cpi!(GameCreated {
+            game: game_account.key(),
+            owner: game_account.owner,
+            club: game_account.club,
+            token_mint: game_account.token_mint,
+            max_players: game_account.max_players,
+            small_blind: game_account.small_blind,
+            big_blind: game_account.big_blind,
+            min_deposit: game_account.min_deposit,
+            max_deposit: game_account.max_deposit,
+        });
         Ok(())
     }
 
-    pub fn join_game(ctx: Context<JoinGame>, data: JoinGameArgs) -> Result<()> {
+    pub fn join_game<'info>(
+        ctx: Context<'_, '_, '_, 'info, JoinGame<'info>>,
+        data: JoinGameArgs,
+    ) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let game_token_account = &mut ctx.accounts.game_token_account;
-        let player_token_account = &mut ctx.accounts.player_token_account;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
         let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        if game_account.status != GameStatus::Open {
+            return Err(PokerError::GameNotActive.into());
+        }
+        check_allowlist(&game_account.club, &ctx.accounts.club, &player.key())?;
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            ctx.accounts.pda_account.to_account_info(),
+        )?;
+        // a seat left free while people are queued goes to them first when
+        // their accounts are passed, as in the payout instructions;
+        let seated = seat_waitlisted(
+            game_account,
+            ctx.remaining_accounts,
+            &game_vault,
+            mint,
+            token_program,
+            system_program,
+        )?;
         if game_account.players.len() == (game_account.max_players as usize) {
             return Err(PokerError::GameFull.into());
         }
@@ -55,42 +186,251 @@ pub mod degods_poker_program {
         {
             return Err(PokerError::AlreadyAtTable.into());
         }
-        let cpi_accounts = Transfer {
-            from: player_token_account.to_account_info().clone(),
-            to: game_token_account.to_account_info().clone(),
-            authority: player.to_account_info().clone(),
-        };
-        let cpi_program = token_program.to_account_info();
-        transfer(CpiContext::new(cpi_program, cpi_accounts), data.amount)?;
+        if data.seat as u16 >= game_account.max_players {
+            return Err(PokerError::InvalidSeat.into());
+        }
+        if game_account.players.iter().any(|p| p.seat == data.seat) {
+            return Err(PokerError::SeatTaken.into());
+        }
+        // credit what the vault actually received, transfer fees come out of the buy-in;
+        let received = deposit_tokens(
+            token_program,
+            system_program,
+            mint,
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            game_vault,
+            player.to_account_info(),
+            &[],
+            data.amount,
+        )?;
+        if received < game_account.min_deposit {
+            return Err(PokerError::DepositTooSmall.into());
+        }
+        let nonce = game_account.statement_nonce;
         game_account.players.push(SeatedPlayer {
             address: player.key(),
+            deposited: received,
+            withdrawn: 0,
+            balance: received,
+            seat: data.seat,
+            sitting_out: false,
+            sat_out_at: 0,
+            nonce,
+        });
+        for joined in seated {
+            emit_cpi!(joined);
+        }
+        emit_cpi!(PlayerJoined {
+            game: game_account.key(),
+            player: player.key(),
+            seat: data.seat,
+            amount: received,
         });
         Ok(())
     }
 
-    pub fn add_chips(ctx: Context<AddChips>, data: AddChipsData) -> Result<()> {
+    pub fn join_waitlist(ctx: Context<JoinWaitlistAccounts>, data: JoinWaitlistArgs) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let game_token_account = &mut ctx.accounts.game_token_account;
-        let player_token_account = &mut ctx.accounts.player_token_account;
+        let waitlist_account = &mut ctx.accounts.waitlist_account;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
         let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        if game_account.status != GameStatus::Open {
+            return Err(PokerError::GameNotActive.into());
+        }
+        check_allowlist(&game_account.club, &ctx.accounts.club, &player.key())?;
+        if game_account.players.len() < (game_account.max_players as usize) {
+            return Err(PokerError::TableNotFull.into());
+        }
+        if game_account.waitlist.len() == MAX_WAITLIST_LEN {
+            return Err(PokerError::WaitlistFull.into());
+        }
+        if data.amount < game_account.min_deposit {
+            return Err(PokerError::DepositTooSmall.into());
+        }
+        if data.amount > game_account.max_deposit {
+            return Err(PokerError::DepositTooLarge.into());
+        }
         if game_account
             .players
             .iter()
-            .all(|p| p.address != player.key())
+            .any(|p| p.address == player.key())
         {
-            return Err(PokerError::NotAtTable.into());
+            return Err(PokerError::AlreadyAtTable.into());
         }
-        if data.amount > game_account.max_deposit {
+        // native SOL escrows sit in the waitlist account itself;
+        let received = deposit_tokens(
+            token_program,
+            system_program,
+            mint,
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            holder(
+                mint,
+                &ctx.accounts.escrow_token_account,
+                waitlist_account.to_account_info(),
+            )?,
+            player.to_account_info(),
+            &[],
+            data.amount,
+        )?;
+        waitlist_account.game = game_account.key();
+        waitlist_account.player = player.key();
+        waitlist_account.amount = received;
+        game_account.waitlist.push(player.key());
+        Ok(())
+    }
+
+    pub fn leave_waitlist(ctx: Context<LeaveWaitlistAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let waitlist_account = &mut ctx.accounts.waitlist_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        game_account.waitlist.retain(|p| *p != player.key());
+        // closing the waitlist account hands back a native SOL escrow;
+        refund_escrow(
+            &game_account.key(),
+            &player.key(),
+            waitlist_account.to_account_info(),
+            ctx.accounts.escrow_token_account.as_ref(),
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            player.to_account_info(),
+            mint,
+            token_program,
+            system_program,
+        )
+    }
+
+    /// Takes a player off the waitlist and refunds their escrow, so an owner
+    /// can clear the queue before closing the table.
+    pub fn remove_from_waitlist(ctx: Context<RemoveFromWaitlistAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        game_account.waitlist.retain(|p| *p != player.key());
+        refund_escrow(
+            &game_account.key(),
+            &player.key(),
+            ctx.accounts.waitlist_account.to_account_info(),
+            ctx.accounts.escrow_token_account.as_ref(),
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            player.to_account_info(),
+            mint,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+        )
+    }
+
+    pub fn set_sitting_out(ctx: Context<SitOutAccounts>, data: SitOutData) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let player = &ctx.accounts.player;
+        let seat = match game_account
+            .players
+            .iter_mut()
+            .find(|p| p.address == player.key())
+        {
+            Some(seat) => seat,
+            None => return Err(PokerError::NotAtTable.into()),
+        };
+        if data.sitting_out && !seat.sitting_out {
+            seat.sat_out_at = Clock::get()?.unix_timestamp;
+        } else if !data.sitting_out {
+            seat.sat_out_at = 0;
+        }
+        seat.sitting_out = data.sitting_out;
+        Ok(())
+    }
+
+    pub fn add_chips(ctx: Context<AddChips>, data: AddChipsData) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        if game_account.status != GameStatus::Open {
+            return Err(PokerError::GameNotActive.into());
+        }
+        let min_deposit = game_account.min_deposit;
+        let max_deposit = game_account.max_deposit;
+        let seat = match game_account
+            .players
+            .iter_mut()
+            .find(|p| p.address == player.key())
+        {
+            Some(seat) => seat,
+            None => return Err(PokerError::NotAtTable.into()),
+        };
+        // table stakes apply to the whole stack, not just the amount being added;
+        let amount = if data.top_up_to_max {
+            max_deposit.saturating_sub(seat.balance)
+        } else {
+            data.amount
+        };
+        let stack = match seat.balance.checked_add(amount) {
+            Some(stack) => stack,
+            None => return Err(PokerError::DepositTooLarge.into()),
+        };
+        if amount == 0 || stack < min_deposit {
+            return Err(PokerError::DepositTooSmall.into());
+        }
+        if stack > max_deposit {
             return Err(PokerError::DepositTooLarge.into());
         }
-        let cpi_accounts = Transfer {
-            from: player_token_account.to_account_info().clone(),
-            to: game_token_account.to_account_info().clone(),
-            authority: player.to_account_info().clone(),
+        let received = deposit_tokens(
+            token_program,
+            system_program,
+            mint,
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            holder(
+                mint,
+                &ctx.accounts.game_token_account,
+                ctx.accounts.pda_account.to_account_info(),
+            )?,
+            player.to_account_info(),
+            &[],
+            amount,
+        )?;
+        seat.deposited = match seat.deposited.checked_add(received) {
+            Some(deposited) => deposited,
+            None => return Err(PokerError::AmountOverflow.into()),
         };
-        let cpi_program = token_program.to_account_info();
-        transfer(CpiContext::new(cpi_program, cpi_accounts), data.amount)?;
+        seat.balance = match seat.balance.checked_add(received) {
+            Some(balance) => balance,
+            None => return Err(PokerError::AmountOverflow.into()),
+        };
+        let balance = seat.balance;
+        emit_cpi!(ChipsAdded {
+            game: game_account.key(),
+            player: player.key(),
+            amount: received,
+            balance,
+        });
         Ok(())
     }
 
@@ -99,41 +439,519 @@ pub mod degods_poker_program {
         data: EjectPlayersParams,
     ) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let game_token_account = &mut ctx.accounts.token_account;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
         let pda_account = &mut ctx.accounts.pda_account;
-        let from = &mut game_token_account.to_account_info().clone();
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        game_account.check_seats_unlocked()?;
+        if game_account.signed_cash_outs {
+            return Err(PokerError::SignedCashOutRequired.into());
+        }
+        let (payees, waitlisted) = split_remaining(ctx.remaining_accounts);
+        if data.amounts.len() != payees.len() {
+            return Err(PokerError::EjectionAmountsMismatch.into());
+        }
+        let from = holder(
+            mint,
+            &ctx.accounts.token_account,
+            pda_account.to_account_info(),
+        )?;
         let authority = &mut pda_account.to_account_info().clone();
-        let cpi_program: AccountInfo<'_> = token_program.to_account_info();
         let game_account_key = game_account.key();
         let (_pda, _bump_seed) =
             Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
         let seed = game_account_key.as_ref();
-        let mut i = 0;
-        for player_token_account in ctx.remaining_accounts.into_iter() {
-            let accounts = Transfer {
-                to: player_token_account.clone(),
-                authority: authority.clone(),
-                from: from.to_account_info().clone(),
+        let token_program_id = token_program.key();
+        let now = Clock::get()?.unix_timestamp;
+        let release_at = if game_account.challenge_window > 0 {
+            now + game_account.challenge_window
+        } else {
+            0
+        };
+        let mut ejected = Vec::new();
+        // payees are player ATAs, or player wallets on native SOL tables;
+        for (player_token_account, &amount) in payees.iter().zip(&data.amounts) {
+            let seat = match game_account.players.iter_mut().find(|p| {
+                payout_address(mint, &token_program_id, &p.address) == player_token_account.key()
+            }) {
+                Some(seat) => seat,
+                None => return Err(PokerError::NotAtTable.into()),
+            };
+            if amount > seat.balance {
+                return Err(PokerError::SeatBalanceTooLow.into());
+            }
+            let (address, nonce) = (seat.address, seat.nonce);
+            seat.withdrawn = match seat.withdrawn.checked_add(amount) {
+                Some(withdrawn) => withdrawn,
+                None => return Err(PokerError::AmountOverflow.into()),
+            };
+            // whatever isn't paid out stays on the seat;
+            seat.balance -= amount;
+            ejected.push(address);
+            // with a challenge window the payout waits in the vault, see release_settlement;
+            if release_at > 0 {
+                game_account.queue_settlement(address, amount, amount, release_at, nonce)?;
+            } else {
+                transfer_tokens(
+                    token_program,
+                    system_program,
+                    mint,
+                    from.clone(),
+                    player_token_account.clone(),
+                    authority.clone(),
+                    &[&[seed, &[_bump_seed]]],
+                    amount,
+                )?;
+            }
+        }
+        // players only leave the table once their whole balance is paid out;
+        game_account
+            .players
+            .retain(|p| p.balance > 0 || !ejected.contains(&p.address));
+        game_account.close_if_empty();
+        game_account.last_activity = now;
+        let seated = seat_waitlisted(
+            game_account,
+            waitlisted,
+            &from,
+            mint,
+            token_program,
+            system_program,
+        )?;
+        game_account.check_vault_covers_seats(vault_balance(mint, &from)?)?;
+        emit_cpi!(PlayersEjected {
+            game: game_account.key(),
+            players: ejected,
+            amounts: data.amounts,
+            release_at,
+        });
+        for joined in seated {
+            emit_cpi!(joined);
+        }
+        Ok(())
+    }
+
+    pub fn eject_sat_out_players<'info>(
+        ctx: Context<'_, '_, '_, 'info, EjectSatOutPlayersAccounts<'info>>,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let pda_account = &mut ctx.accounts.pda_account;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        game_account.check_seats_unlocked()?;
+        if game_account.signed_cash_outs {
+            return Err(PokerError::SignedCashOutRequired.into());
+        }
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.token_account,
+            pda_account.to_account_info(),
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        let release_at = if game_account.challenge_window > 0 {
+            now + game_account.challenge_window
+        } else {
+            0
+        };
+        let game_account_key = game_account.key();
+        let (_pda, _bump_seed) =
+            Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
+        let seed = game_account_key.as_ref();
+        let token_program_id = token_program.key();
+        let mut players = Vec::new();
+        let mut amounts = Vec::new();
+        let (payees, waitlisted) = split_remaining(ctx.remaining_accounts);
+        for player_token_account in payees.iter() {
+            let index = match game_account.players.iter().position(|p| {
+                payout_address(mint, &token_program_id, &p.address) == player_token_account.key()
+            }) {
+                Some(index) => index,
+                None => return Err(PokerError::NotAtTable.into()),
             };
-            transfer(
-                CpiContext::new_with_signer(
-                    cpi_program.clone(),
-                    accounts,
+            let seat = &game_account.players[index];
+            // a limit of zero turns auto-ejection off;
+            if game_account.max_sit_out_seconds == 0 {
+                return Err(PokerError::SitOutLimitNotReached.into());
+            }
+            // players who came back or haven't been out long enough are
+            // skipped so one of them doesn't sink the whole batch;
+            if !seat.sitting_out || now - seat.sat_out_at < game_account.max_sit_out_seconds {
+                continue;
+            }
+            let seat = game_account.players.remove(index);
+            players.push(seat.address);
+            amounts.push(seat.balance);
+            // held for the challenge window like any other ejection;
+            if release_at > 0 {
+                game_account.queue_settlement(
+                    seat.address,
+                    seat.balance,
+                    seat.balance,
+                    release_at,
+                    seat.nonce,
+                )?;
+            } else if seat.balance > 0 {
+                transfer_tokens(
+                    token_program,
+                    system_program,
+                    mint,
+                    game_vault.clone(),
+                    player_token_account.clone(),
+                    pda_account.to_account_info(),
                     &[&[seed, &[_bump_seed]]],
-                ),
-                data.amounts[i],
+                    seat.balance,
+                )?;
+            }
+        }
+        game_account.close_if_empty();
+        game_account.last_activity = now;
+        let seated = seat_waitlisted(
+            game_account,
+            waitlisted,
+            &game_vault,
+            mint,
+            token_program,
+            system_program,
+        )?;
+        game_account.check_vault_covers_seats(vault_balance(mint, &game_vault)?)?;
+        emit_cpi!(PlayersEjected {
+            game: game_account.key(),
+            players,
+            amounts,
+            release_at,
+        });
+        for joined in seated {
+            emit_cpi!(joined);
+        }
+        Ok(())
+    }
+
+    /// An ejected player contests their payout before the challenge window
+    /// closes, signing the claim like a balance statement, see `statement`.
+    /// It stays frozen in the vault until the arbiter rules, or until
+    /// `ARBITER_TIMEOUT` passes and it can be released as reported.
+    pub fn dispute_settlement(
+        ctx: Context<DisputeSettlementAccounts>,
+        data: DisputeSettlementData,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let player = &ctx.accounts.player;
+        let now = Clock::get()?.unix_timestamp;
+        let game_account_key = game_account.key();
+        let settlement = match game_account
+            .pending_settlements
+            .iter_mut()
+            .find(|s| s.player == player.key())
+        {
+            Some(settlement) => settlement,
+            None => return Err(PokerError::NoPendingSettlement.into()),
+        };
+        if settlement.disputed {
+            return Err(PokerError::AlreadyDisputed.into());
+        }
+        if now >= settlement.release_at {
+            return Err(PokerError::ChallengeWindowClosed.into());
+        }
+        let message = statement::dispute_message(
+            &game_account_key,
+            settlement.nonce,
+            data.claimed,
+            &data.evidence,
+        );
+        if !statement::signers(&ctx.accounts.instructions, &message).contains(&player.key()) {
+            return Err(PokerError::MissingStatementSignature.into());
+        }
+        settlement.disputed = true;
+        settlement.claimed = data.claimed;
+        settlement.evidence = data.evidence;
+        settlement.release_at = now + ARBITER_TIMEOUT;
+        let (amount, rule_by) = (settlement.amount, settlement.release_at);
+        emit_cpi!(SettlementDisputed {
+            game: game_account_key,
+            player: player.key(),
+            amount,
+            claimed: data.claimed,
+            evidence: data.evidence,
+            rule_by,
+        });
+        Ok(())
+    }
+
+    /// Anyone can pay out a settlement once its window has passed, and a
+    /// disputed one as reported once the arbiter's deadline has.
+    pub fn release_settlement(ctx: Context<PaySettlementAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let pda_account = &mut ctx.accounts.pda_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            pda_account.to_account_info(),
+        )?;
+        let index = match game_account
+            .pending_settlements
+            .iter()
+            .position(|s| s.player == player.key())
+        {
+            Some(index) => index,
+            None => return Err(PokerError::NoPendingSettlement.into()),
+        };
+        let settlement = &game_account.pending_settlements[index];
+        let now = Clock::get()?.unix_timestamp;
+        if now < settlement.release_at {
+            if settlement.disputed {
+                return Err(PokerError::PayoutDisputed.into());
+            }
+            return Err(PokerError::ChallengeWindowOpen.into());
+        }
+        let settlement = game_account.pending_settlements.remove(index);
+        let returned = settlement.held - settlement.amount;
+        game_account.return_held(player.key(), returned, settlement.nonce, now)?;
+        let game_account_key = game_account.key();
+        let seed = game_account_key.as_ref();
+        let (_pda, _bump_seed) =
+            Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
+        if settlement.amount > 0 {
+            transfer_tokens(
+                token_program,
+                system_program,
+                mint,
+                game_vault.clone(),
+                holder(
+                    mint,
+                    &ctx.accounts.player_token_account,
+                    player.to_account_info(),
+                )?,
+                pda_account.to_account_info(),
+                &[&[seed, &[_bump_seed]]],
+                settlement.amount,
+            )?;
+        }
+        game_account.check_vault_covers_seats(vault_balance(mint, &game_vault)?)?;
+        emit_cpi!(SettlementPaid {
+            game: game_account.key(),
+            player: player.key(),
+            amount: settlement.amount,
+            disputed: settlement.disputed,
+            returned,
+        });
+        Ok(())
+    }
+
+    /// The arbiter's ruling on a disputed payout, paid out on the spot. Whatever
+    /// was held back beyond `amount` goes back to the player, see `return_held`.
+    pub fn resolve_dispute(
+        ctx: Context<PaySettlementAccounts>,
+        data: ResolveDisputeData,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let pda_account = &mut ctx.accounts.pda_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let payer = &ctx.accounts.payer;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        if payer.key() != game_account.arbiter {
+            return Err(PokerError::NotArbiter.into());
+        }
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            pda_account.to_account_info(),
+        )?;
+        let index = match game_account
+            .pending_settlements
+            .iter()
+            .position(|s| s.player == player.key())
+        {
+            Some(index) => index,
+            None => return Err(PokerError::NoPendingSettlement.into()),
+        };
+        let settlement = &game_account.pending_settlements[index];
+        if !settlement.disputed {
+            return Err(PokerError::NotDisputed.into());
+        }
+        if data.amount > settlement.held {
+            return Err(PokerError::InvalidSettlementAmount.into());
+        }
+        let settlement = game_account.pending_settlements.remove(index);
+        let now = Clock::get()?.unix_timestamp;
+        let returned = settlement.held - data.amount;
+        game_account.return_held(player.key(), returned, settlement.nonce, now)?;
+        let game_account_key = game_account.key();
+        let seed = game_account_key.as_ref();
+        let (_pda, _bump_seed) =
+            Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
+        if data.amount > 0 {
+            transfer_tokens(
+                token_program,
+                system_program,
+                mint,
+                game_vault.clone(),
+                holder(
+                    mint,
+                    &ctx.accounts.player_token_account,
+                    player.to_account_info(),
+                )?,
+                pda_account.to_account_info(),
+                &[&[seed, &[_bump_seed]]],
+                data.amount,
+            )?;
+        }
+        game_account.last_activity = now;
+        game_account.check_vault_covers_seats(vault_balance(mint, &game_vault)?)?;
+        emit_cpi!(SettlementPaid {
+            game: game_account.key(),
+            player: player.key(),
+            amount: data.amount,
+            disputed: true,
+            returned,
+        });
+        Ok(())
+    }
+
+    /// Pays a player out on a balance statement signed by them and by the
+    /// table's owner or transactor, see `statement`. Anyone holding the
+    /// statement can submit it. With a challenge window the payout is held
+    /// like an ejection, and a newer statement can replace it until the
+    /// window closes.
+    pub fn cash_out_signed<'info>(
+        ctx: Context<'_, '_, '_, 'info, SignedCashOutAccounts<'info>>,
+        data: BalanceStatement,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let pda_account = &mut ctx.accounts.pda_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            pda_account.to_account_info(),
+        )?;
+        let message =
+            statement::balance_message(&game_account.key(), data.seat, data.nonce, data.balance);
+        let signers = statement::signers(&ctx.accounts.instructions, &message);
+        if !signers.contains(&player.key()) || !signers.iter().any(|s| game_account.is_operator(s))
+        {
+            return Err(PokerError::MissingStatementSignature.into());
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let mut release_at = 0;
+        match game_account
+            .players
+            .iter()
+            .position(|p| p.address == player.key())
+        {
+            Some(index) => {
+                let seat = &game_account.players[index];
+                if seat.seat != data.seat {
+                    return Err(PokerError::SeatMismatch.into());
+                }
+                if data.nonce <= seat.nonce {
+                    return Err(PokerError::StaleStatement.into());
+                }
+                // chips committed to the current hand aren't settled yet;
+                if game_account.hand_in_progress {
+                    return Err(PokerError::HandInProgress.into());
+                }
+                if game_account
+                    .pending_settlements
+                    .iter()
+                    .any(|s| s.player == player.key())
+                {
+                    return Err(PokerError::SettlementsPending.into());
+                }
+                game_account.players.remove(index);
+                game_account.close_if_empty();
+                if game_account.challenge_window > 0 {
+                    release_at = now + game_account.challenge_window;
+                    game_account.queue_settlement(
+                        player.key(),
+                        data.balance,
+                        data.balance,
+                        release_at,
+                        data.nonce,
+                    )?;
+                }
+            }
+            // already left, a newer statement replaces the held payout;
+            None => {
+                let pending = match game_account
+                    .pending_settlements
+                    .iter_mut()
+                    .find(|s| s.player == player.key())
+                {
+                    Some(pending) => pending,
+                    None => return Err(PokerError::NotAtTable.into()),
+                };
+                if pending.disputed {
+                    return Err(PokerError::PayoutDisputed.into());
+                }
+                if now >= pending.release_at {
+                    return Err(PokerError::ChallengeWindowClosed.into());
+                }
+                if data.nonce <= pending.nonce {
+                    return Err(PokerError::StaleStatement.into());
+                }
+                pending.amount = data.balance;
+                pending.held = data.balance;
+                pending.nonce = data.nonce;
+                release_at = pending.release_at;
+            }
+        }
+        game_account.statement_nonce = game_account.statement_nonce.max(data.nonce);
+        let game_account_key = game_account.key();
+        let seed = game_account_key.as_ref();
+        let (_pda, _bump_seed) =
+            Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
+        if release_at == 0 && data.balance > 0 {
+            transfer_tokens(
+                token_program,
+                system_program,
+                mint,
+                game_vault.clone(),
+                holder(
+                    mint,
+                    &ctx.accounts.player_token_account,
+                    player.to_account_info(),
+                )?,
+                pda_account.to_account_info(),
+                &[&[seed, &[_bump_seed]]],
+                data.balance,
             )?;
-            i += 1;
         }
-        game_account.players.retain(|p| {
-            let token_addr =
-                get_associated_token_address(&p.address.key(), &game_token_account.mint.key());
-            return !ctx
-                .remaining_accounts
-                .into_iter()
-                .any(|r| r.key() == token_addr);
+        // remaining accounts, if any, are the waitlisted players taking the freed seat;
+        let seated = seat_waitlisted(
+            game_account,
+            ctx.remaining_accounts,
+            &game_vault,
+            mint,
+            token_program,
+            system_program,
+        )?;
+        game_account.check_vault_covers_seats(vault_balance(mint, &game_vault)?)?;
+        emit_cpi!(SignedCashOut {
+            game: game_account.key(),
+            player: player.key(),
+            seat: data.seat,
+            nonce: data.nonce,
+            balance: data.balance,
+            release_at,
         });
+        for joined in seated {
+            emit_cpi!(joined);
+        }
         Ok(())
     }
 
@@ -142,85 +960,943 @@ pub mod degods_poker_program {
         data: RefundPlayerParams,
     ) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let game_token_account = &mut ctx.accounts.game_token_account;
-        let player_token_account = &mut ctx.accounts.player_token_account;
         let pda_account = &mut ctx.accounts.pda_account;
         let authority = &mut pda_account.to_account_info().clone();
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
         let payer = &ctx.accounts.payer;
-        if payer.key() != game_account.owner {
-            return Err(PokerError::NotGameOwner.into());
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        game_account.check_seats_unlocked()?;
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            pda_account.to_account_info(),
+        )?;
+        if !game_account.is_operator(&payer.key())
+            && !has_role(
+                &ctx.accounts.role,
+                &game_account.key(),
+                &game_account.club,
+                &payer.key(),
+                Permission::Pay,
+            )
+        {
+            return Err(PokerError::NotGameOperator.into());
         }
-        if game_account
+        let seat = match game_account
             .players
-            .iter()
-            .all(|p| p.address != player_token_account.owner.key())
+            .iter_mut()
+            .find(|p| p.address == player.key())
         {
-            return Err(PokerError::NotAtTable.into());
+            Some(seat) => seat,
+            None => return Err(PokerError::NotAtTable.into()),
+        };
+        seat.balance = match seat.balance.checked_sub(data.amount) {
+            Some(balance) => balance,
+            None => return Err(PokerError::SeatBalanceTooLow.into()),
+        };
+        seat.withdrawn = match seat.withdrawn.checked_add(data.amount) {
+            Some(withdrawn) => withdrawn,
+            None => return Err(PokerError::AmountOverflow.into()),
+        };
+        let balance = seat.balance;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        let game_account_key = game_account.key();
+        let (_pda, _bump_seed) =
+            Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
+        let seed = game_account_key.as_ref();
+        transfer_tokens(
+            token_program,
+            system_program,
+            mint,
+            game_vault.clone(),
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            authority.to_account_info(),
+            &[&[seed, &[_bump_seed]]],
+            data.amount,
+        )?;
+        game_account.check_vault_covers_seats(vault_balance(mint, &game_vault)?)?;
+        emit_cpi!(PlayerRefunded {
+            game: game_account.key(),
+            player: player.key(),
+            amount: data.amount,
+            balance,
+        });
+        Ok(())
+    }
+
+    pub fn leave_table<'info>(
+        ctx: Context<'_, '_, '_, 'info, LeaveTableAccounts<'info>>,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let pda_account = &mut ctx.accounts.pda_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            pda_account.to_account_info(),
+        )?;
+        // chips committed to the current hand aren't settled yet;
+        if game_account.hand_in_progress {
+            return Err(PokerError::HandInProgress.into());
         }
-        let cpi_accounts = Transfer {
-            from: game_token_account.to_account_info().clone(),
-            to: player_token_account.to_account_info().clone(),
-            authority: authority.to_account_info().clone(),
+        let index = match game_account
+            .players
+            .iter()
+            .position(|p| p.address == player.key())
+        {
+            Some(index) => index,
+            None => return Err(PokerError::NotAtTable.into()),
         };
-        let cpi_program = token_program.to_account_info();
+        let seat = game_account.players.remove(index);
+        game_account.close_if_empty();
         let game_account_key = game_account.key();
+        let seed = game_account_key.as_ref();
         let (_pda, _bump_seed) =
             Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
+        if seat.balance > 0 {
+            transfer_tokens(
+                token_program,
+                system_program,
+                mint,
+                game_vault.clone(),
+                holder(
+                    mint,
+                    &ctx.accounts.player_token_account,
+                    player.to_account_info(),
+                )?,
+                pda_account.to_account_info(),
+                &[&[seed, &[_bump_seed]]],
+                seat.balance,
+            )?;
+        }
+        // remaining accounts, if any, are the waitlisted players taking the freed seat;
+        let seated = seat_waitlisted(
+            game_account,
+            ctx.remaining_accounts,
+            &game_vault,
+            mint,
+            token_program,
+            system_program,
+        )?;
+        game_account.check_vault_covers_seats(vault_balance(mint, &game_vault)?)?;
+        emit_cpi!(PlayerLeft {
+            game: game_account.key(),
+            player: player.key(),
+            amount: seat.balance,
+            abandoned: false,
+        });
+        for joined in seated {
+            emit_cpi!(joined);
+        }
+        Ok(())
+    }
+
+    pub fn withdraw_abandoned(ctx: Context<LeaveTableAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let pda_account = &mut ctx.accounts.pda_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            pda_account.to_account_info(),
+        )?;
+        // a stuck hand doesn't lock the seats here, the player gets their last
+        // settled balance;
+        if !game_account.is_abandoned(Clock::get()?.unix_timestamp) {
+            return Err(PokerError::GameNotAbandoned.into());
+        }
+        // an on-chain hand is called off first, handing back what each player
+        // put in;
+        if game_account.hand_in_progress && game_account.mode != GameMode::Dealer {
+            let hand_account = match &mut ctx.accounts.hand_account {
+                Some(hand_account) => hand_account,
+                None => return Err(PokerError::HandInProgress.into()),
+            };
+            let (payouts, rake) = finish_hand(game_account, hand_account, Outcome::Void)?;
+            emit_cpi!(HandFinished {
+                game: game_account.key(),
+                hand: hand_account.hand,
+                players: hand_account.players,
+                payouts,
+                rake,
+                board: hand_account.board.clone(),
+                hole_cards: hand_account.hole_cards,
+                hole_cards_revealed: hand_account.hole_cards_revealed,
+                voided: true,
+            });
+        }
+        let index = match game_account
+            .players
+            .iter()
+            .position(|p| p.address == player.key())
+        {
+            Some(index) => index,
+            None => return Err(PokerError::NotAtTable.into()),
+        };
+        let seat = game_account.players.remove(index);
+        game_account.close_if_empty();
+        let game_account_key = game_account.key();
         let seed = game_account_key.as_ref();
-        transfer(
-            CpiContext::new_with_signer(
-                cpi_program.clone(),
-                cpi_accounts,
+        let (_pda, _bump_seed) =
+            Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
+        if seat.balance > 0 {
+            transfer_tokens(
+                token_program,
+                system_program,
+                mint,
+                game_vault.clone(),
+                holder(
+                    mint,
+                    &ctx.accounts.player_token_account,
+                    player.to_account_info(),
+                )?,
+                pda_account.to_account_info(),
                 &[&[seed, &[_bump_seed]]],
-            ),
-            data.amount,
+                seat.balance,
+            )?;
+        }
+        game_account.check_vault_covers_seats(vault_balance(mint, &game_vault)?)?;
+        emit_cpi!(PlayerLeft {
+            game: game_account.key(),
+            player: player.key(),
+            amount: seat.balance,
+            abandoned: true,
+        });
+        Ok(())
+    }
+
+    pub fn start_hand(ctx: Context<StartHandAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        if game_account.mode != GameMode::Dealer {
+            return Err(PokerError::InvalidGameMode.into());
+        }
+        if game_account.hand_in_progress {
+            return Err(PokerError::HandInProgress.into());
+        }
+        let shuffle_account = &mut ctx.accounts.shuffle_account;
+        if shuffle_account.phase != ShufflePhase::Committing {
+            return Err(PokerError::InvalidShufflePhase.into());
+        }
+        if shuffle_account.missing_commitments(game_account) {
+            return Err(PokerError::MissingShuffleCommitment.into());
+        }
+        shuffle_account.phase = ShufflePhase::Dealt;
+        game_account.hand_in_progress = true;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    pub fn settle_hand(ctx: Context<SettleHandAccounts>, data: SettleHandData) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        if game_account.mode != GameMode::Dealer {
+            return Err(PokerError::InvalidGameMode.into());
+        }
+        // start_hand locks a committed shuffle round before setting this;
+        if !game_account.hand_in_progress {
+            return Err(PokerError::NoHandInProgress.into());
+        }
+        let net = data.deltas.iter().map(|d| d.delta as i128).sum::<i128>() + data.rake as i128;
+        if net != 0 {
+            return Err(PokerError::UnbalancedSettlement.into());
+        }
+        // the pot the rake is taken from is whatever the losing seats put in;
+        let pot = match data
+            .deltas
+            .iter()
+            .filter(|d| d.delta < 0)
+            .try_fold(0u64, |pot, d| pot.checked_add(d.delta.unsigned_abs()))
+        {
+            Some(pot) => pot,
+            None => return Err(PokerError::AmountOverflow.into()),
+        };
+        if data.rake > game_account.max_rake(pot) {
+            return Err(PokerError::RakeTooLarge.into());
+        }
+        for (i, d) in data.deltas.iter().enumerate() {
+            if data.deltas[..i].iter().any(|o| o.player == d.player) {
+                return Err(PokerError::DuplicatePlayer.into());
+            }
+            let seat = match game_account
+                .players
+                .iter_mut()
+                .find(|p| p.address == d.player)
+            {
+                Some(seat) => seat,
+                None => return Err(PokerError::NotAtTable.into()),
+            };
+            seat.balance = match seat.balance.checked_add_signed(d.delta) {
+                Some(balance) => balance,
+                None => return Err(PokerError::SeatBalanceTooLow.into()),
+            };
+        }
+        game_account.rake_accrued = match game_account.rake_accrued.checked_add(data.rake) {
+            Some(rake_accrued) => rake_accrued,
+            None => return Err(PokerError::AmountOverflow.into()),
+        };
+        game_account.hand_in_progress = false;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        if let Some(hand_hash) = &data.hand_hash {
+            game_account.commit_hand(hand_hash);
+        }
+        emit_cpi!(HandSettled {
+            game: game_account.key(),
+            deltas: data.deltas,
+            rake: data.rake,
+            hand_history: game_account.hand_history,
+        });
+        Ok(())
+    }
+
+    /// For dealers that settle first and hash the hand log afterwards.
+    pub fn commit_hand_history(
+        ctx: Context<SettleHandAccounts>,
+        data: CommitHandData,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        game_account.commit_hand(&data.hand_hash);
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        emit_cpi!(HandCommitted {
+            game: game_account.key(),
+            hand_hash: data.hand_hash,
+            hand_history: game_account.hand_history,
+            hands_committed: game_account.hands_committed,
+        });
+        Ok(())
+    }
+
+    /// Starts the next hand's commit-reveal round with the dealer's commitment.
+    /// The previous round has to be fully revealed or out of time; in the
+    /// latter case seated players who kept their seed are sat out, and a
+    /// dealer that kept its own can't open another.
+    pub fn open_shuffle(ctx: Context<OpenShuffleAccounts>, data: ShuffleSeedData) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let shuffle_account = &mut ctx.accounts.shuffle_account;
+        if game_account.hand_in_progress {
+            return Err(PokerError::HandInProgress.into());
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let mut sat_out = Vec::new();
+        // a new account has no previous round;
+        if shuffle_account.game != Pubkey::default() {
+            match shuffle_account.phase {
+                ShufflePhase::Revealed => {}
+                ShufflePhase::Dealt if !shuffle_account.parties[0].revealed => {
+                    return Err(PokerError::DealerSeedMissing.into());
+                }
+                ShufflePhase::Dealt if now < shuffle_account.reveal_deadline => {
+                    return Err(PokerError::RevealWindowOpen.into());
+                }
+                ShufflePhase::Dealt => {
+                    for party in shuffle_account.parties.iter().filter(|c| !c.revealed) {
+                        if let Some(seat) = game_account
+                            .players
+                            .iter_mut()
+                            .find(|p| p.address == party.party && !p.sitting_out)
+                        {
+                            seat.sitting_out = true;
+                            seat.sat_out_at = now;
+                            sat_out.push(seat.address);
+                        }
+                    }
+                }
+                ShufflePhase::Committing => return Err(PokerError::InvalidShufflePhase.into()),
+            }
+        }
+        shuffle_account.game = game_account.key();
+        shuffle_account.hand += 1;
+        shuffle_account.phase = ShufflePhase::Committing;
+        shuffle_account.parties = vec![ShuffleParty {
+            party: ctx.accounts.payer.key(),
+            commitment: data.value,
+            seed: [0; 32],
+            revealed: false,
+        }];
+        shuffle_account.deck_seed = [0; 32];
+        shuffle_account.reveal_deadline = 0;
+        emit_cpi!(ShuffleOpened {
+            game: game_account.key(),
+            hand: shuffle_account.hand,
+            dealer_commitment: data.value,
+            sat_out,
+        });
+        Ok(())
+    }
+
+    pub fn commit_shuffle_seed(
+        ctx: Context<ShuffleSeedAccounts>,
+        data: ShuffleSeedData,
+    ) -> Result<()> {
+        let game_account = &ctx.accounts.game_account;
+        let shuffle_account = &mut ctx.accounts.shuffle_account;
+        let party = &ctx.accounts.party;
+        if shuffle_account.phase != ShufflePhase::Committing {
+            return Err(PokerError::InvalidShufflePhase.into());
+        }
+        if !game_account
+            .players
+            .iter()
+            .any(|p| p.address == party.key())
+        {
+            return Err(PokerError::NotAtTable.into());
+        }
+        if shuffle_account
+            .parties
+            .iter()
+            .any(|c| c.party == party.key())
+        {
+            return Err(PokerError::AlreadyCommitted.into());
+        }
+        if shuffle_account.parties.len() >= MAX_SHUFFLE_PARTIES {
+            return Err(PokerError::ShufflePartiesFull.into());
+        }
+        shuffle_account.parties.push(ShuffleParty {
+            party: party.key(),
+            commitment: data.value,
+            seed: [0; 32],
+            revealed: false,
+        });
+        Ok(())
+    }
+
+    pub fn reveal_shuffle_seed(
+        ctx: Context<ShuffleSeedAccounts>,
+        data: ShuffleSeedData,
+    ) -> Result<()> {
+        let game_account = &ctx.accounts.game_account;
+        let shuffle_account = &mut ctx.accounts.shuffle_account;
+        let party = &ctx.accounts.party;
+        // seeds stay secret until the hand they shuffled is over; a heads-up
+        // hand waits on them to be paid;
+        let betting_over = match &ctx.accounts.hand_account {
+            Some(hand_account) => hand_account.outcome.is_some(),
+            None => false,
+        };
+        if shuffle_account.phase != ShufflePhase::Dealt
+            || (game_account.hand_in_progress && !betting_over)
+        {
+            return Err(PokerError::InvalidShufflePhase.into());
+        }
+        let entry = match shuffle_account
+            .parties
+            .iter_mut()
+            .find(|c| c.party == party.key())
+        {
+            Some(entry) => entry,
+            None => return Err(PokerError::NotShuffleParty.into()),
+        };
+        if shuffle::commitment(&data.value) != entry.commitment {
+            return Err(PokerError::ShuffleSeedMismatch.into());
+        }
+        entry.seed = data.value;
+        entry.revealed = true;
+        if shuffle_account.reveal_deadline == 0 {
+            shuffle_account.reveal_deadline = Clock::get()?.unix_timestamp + SHUFFLE_REVEAL_TIMEOUT;
+        }
+        if shuffle_account.parties.iter().all(|c| c.revealed) {
+            let seeds: Vec<[u8; 32]> = shuffle_account.parties.iter().map(|c| c.seed).collect();
+            shuffle_account.deck_seed = shuffle::deck_seed(&seeds);
+            shuffle_account.phase = ShufflePhase::Revealed;
+            emit_cpi!(ShuffleRevealed {
+                game: game_account.key(),
+                hand: shuffle_account.hand,
+                deck_seed: shuffle_account.deck_seed,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn deal_hand(ctx: Context<DealHandAccounts>, data: DealHandData) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let hand_account = &mut ctx.accounts.hand_account;
+        let shuffle_account = &mut ctx.accounts.shuffle_account;
+        let action_timeout = game_account.action_timeout()?;
+        if game_account.hand_in_progress {
+            return Err(PokerError::HandInProgress.into());
+        }
+        if game_account.players.len() != 2
+            || game_account
+                .players
+                .iter()
+                .any(|p| p.sitting_out || p.balance == 0)
+        {
+            return Err(PokerError::NeedTwoPlayers.into());
+        }
+        if shuffle_account.phase != ShufflePhase::Committing {
+            return Err(PokerError::InvalidShufflePhase.into());
+        }
+        if shuffle_account.missing_commitments(game_account) {
+            return Err(PokerError::MissingShuffleCommitment.into());
+        }
+        shuffle_account.phase = ShufflePhase::Dealt;
+        // the button moves to whoever had the big blind last hand;
+        let seats = &game_account.players;
+        let button = if seats[1].address == hand_account.players[1] {
+            1
+        } else {
+            0
+        };
+        let (first, second) = (&seats[button], &seats[1 - button]);
+        let now = Clock::get()?.unix_timestamp;
+        hand_account.game = game_account.key();
+        hand_account.card_commitments = data.card_commitments;
+        hand_account.sealed_hole_cards = data.sealed_hole_cards;
+        hand_account.deal(
+            [first.address, second.address],
+            [first.balance, second.balance],
+            game_account.small_blind,
+            game_account.big_blind,
+            now + action_timeout,
+        );
+        game_account.hand_in_progress = true;
+        game_account.last_activity = now;
+        emit_cpi!(HandDealt {
+            game: game_account.key(),
+            hand: hand_account.hand,
+            players: hand_account.players,
+            card_commitments: hand_account.card_commitments,
+        });
+        Ok(())
+    }
+
+    pub fn player_action(ctx: Context<HandActionAccounts>, data: PlayerActionData) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let hand_account = &mut ctx.accounts.hand_account;
+        let payer = &ctx.accounts.payer;
+        let action_timeout = game_account.action_timeout()?;
+        let player = match hand_account.players.iter().position(|p| *p == payer.key()) {
+            Some(player) => player,
+            None => return Err(PokerError::NotAtTable.into()),
+        };
+        let now = Clock::get()?.unix_timestamp;
+        let outcome = hand_account.act(player, data.action, now + action_timeout)?;
+        game_account.last_activity = now;
+        emit_cpi!(PlayerActed {
+            game: game_account.key(),
+            hand: hand_account.hand,
+            player: payer.key(),
+            action: data.action,
+            pot: hand_account.pot(),
+        });
+        if let Some(outcome) = outcome {
+            hand_account.conclude(outcome, now + action_timeout);
+        }
+        Ok(())
+    }
+
+    pub fn reveal_board(ctx: Context<RevealBoardAccounts>, data: RevealBoardData) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let hand_account = &mut ctx.accounts.hand_account;
+        let action_timeout = game_account.action_timeout()?;
+        let now = Clock::get()?.unix_timestamp;
+        hand_account.reveal_board(&data.cards, now + action_timeout)?;
+        game_account.last_activity = now;
+        emit_cpi!(BoardRevealed {
+            game: game_account.key(),
+            hand: hand_account.hand,
+            board: hand_account.board.clone(),
+        });
+        Ok(())
+    }
+
+    /// Either the player or the dealer, who holds every salt, can show a hand.
+    pub fn reveal_hole_cards(
+        ctx: Context<HandActionAccounts>,
+        data: RevealHoleCardsData,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let hand_account = &mut ctx.accounts.hand_account;
+        let payer = &ctx.accounts.payer;
+        let action_timeout = game_account.action_timeout()?;
+        if payer.key() != data.player
+            && !game_account.is_operator(&payer.key())
+            && !has_role(
+                &ctx.accounts.role,
+                &game_account.key(),
+                &game_account.club,
+                &payer.key(),
+                Permission::Deal,
+            )
+        {
+            return Err(PokerError::NotGameOperator.into());
+        }
+        let player = match hand_account.players.iter().position(|p| *p == data.player) {
+            Some(player) => player,
+            None => return Err(PokerError::NotAtTable.into()),
+        };
+        let outcome = hand_account.reveal_hole_cards(player, &data.cards)?;
+        let now = Clock::get()?.unix_timestamp;
+        game_account.last_activity = now;
+        if let Some(outcome) = outcome {
+            hand_account.conclude(outcome, now + action_timeout);
+        }
+        Ok(())
+    }
+
+    /// Anyone can push a hand along once whoever it's waiting on runs out of time.
+    pub fn claim_hand_timeout(ctx: Context<HandActionAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let hand_account = &mut ctx.accounts.hand_account;
+        let action_timeout = game_account.action_timeout()?;
+        let now = Clock::get()?.unix_timestamp;
+        if hand_account.street == Street::Complete {
+            return Err(PokerError::InvalidStreet.into());
+        }
+        if now < hand_account.action_deadline {
+            return Err(PokerError::ActionClockRunning.into());
+        }
+        let outcome = hand_account.time_out(now + action_timeout)?;
+        if let Some(outcome) = outcome {
+            hand_account.conclude(outcome, now + action_timeout);
+        }
+        Ok(())
+    }
+
+    /// Pays out a heads-up hand once its round's seeds are public, voiding it
+    /// if any card shown isn't where `shuffle::permutation` put it. When the
+    /// reveal clock runs out first, a player who kept their seed forfeits and
+    /// a dealer who kept its own voids the hand.
+    pub fn settle_heads_up_hand(ctx: Context<SettleHeadsUpHandAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let hand_account = &mut ctx.accounts.hand_account;
+        let shuffle_account = &ctx.accounts.shuffle_account;
+        game_account.action_timeout()?;
+        let now = Clock::get()?.unix_timestamp;
+        let outcome = match hand_account.outcome {
+            Some(Outcome::Void) => Outcome::Void,
+            Some(outcome) if shuffle_account.phase == ShufflePhase::Revealed => {
+                let deck = shuffle::permutation(
+                    &shuffle_account.deck_seed,
+                    shuffle::deck_size(game_account.variant),
+                );
+                if hand_account.dealt_from(&deck) {
+                    outcome
+                } else {
+                    Outcome::Void
+                }
+            }
+            Some(_) if now < hand_account.action_deadline => {
+                return Err(PokerError::RevealWindowOpen.into());
+            }
+            Some(_) => hand_account.forfeit(shuffle_account),
+            None => return Err(PokerError::InvalidStreet.into()),
+        };
+        let (payouts, rake) = finish_hand(game_account, hand_account, outcome)?;
+        game_account.last_activity = now;
+        emit_cpi!(HandFinished {
+            game: game_account.key(),
+            hand: hand_account.hand,
+            players: hand_account.players,
+            payouts,
+            rake,
+            board: hand_account.board.clone(),
+            hole_cards: hand_account.hole_cards,
+            hole_cards_revealed: hand_account.hole_cards_revealed,
+            voided: outcome == Outcome::Void,
+        });
+        Ok(())
+    }
+
+    pub fn withdraw_rake(ctx: Context<WithdrawRakeAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let pda_account = &mut ctx.accounts.pda_account;
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            pda_account.to_account_info(),
+        )?;
+        let game_account_key = game_account.key();
+        let seed = game_account_key.as_ref();
+        let (_pda, _bump_seed) =
+            Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
+        let amount = game_account.rake_accrued;
+        transfer_tokens(
+            token_program,
+            system_program,
+            mint,
+            game_vault.clone(),
+            holder(
+                mint,
+                &ctx.accounts.rake_recipient_token_account,
+                ctx.accounts.rake_recipient.to_account_info(),
+            )?,
+            pda_account.to_account_info(),
+            &[&[seed, &[_bump_seed]]],
+            amount,
         )?;
+        game_account.rake_accrued = 0;
+        game_account.check_vault_covers_seats(vault_balance(mint, &game_vault)?)?;
+        emit_cpi!(RakeWithdrawn {
+            game: game_account.key(),
+            recipient: game_account.rake_recipient,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn set_game_transactor(
+        ctx: Context<SetGameTransactorAccounts>,
+        data: SetTransactorData,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        game_account.transactor = data.transactor;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    pub fn set_dispute_settings(
+        ctx: Context<DisputeSettingsAccounts>,
+        data: DisputeSettingsData,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        if data.challenge_window < 0 {
+            return Err(PokerError::InvalidChallengeWindow.into());
+        }
+        // a dispute nobody can resolve would freeze the payout for good;
+        if data.challenge_window > 0 && data.arbiter == Pubkey::default() {
+            return Err(PokerError::InvalidArbiter.into());
+        }
+        game_account.challenge_window = data.challenge_window;
+        game_account.arbiter = data.arbiter;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    pub fn set_signed_cash_outs(
+        ctx: Context<SignedCashOutSettingsAccounts>,
+        data: SignedCashOutSettingsData,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        game_account.signed_cash_outs = data.required;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    pub fn pause_game(ctx: Context<GameStatusAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        if game_account.status != GameStatus::Open {
+            return Err(PokerError::InvalidGameStatus.into());
+        }
+        game_account.status = GameStatus::Paused;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        emit_cpi!(GameStatusChanged {
+            game: game_account.key(),
+            status: game_account.status,
+        });
+        Ok(())
+    }
+
+    pub fn resume_game(ctx: Context<GameStatusAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        if game_account.status != GameStatus::Paused {
+            return Err(PokerError::InvalidGameStatus.into());
+        }
+        game_account.status = GameStatus::Open;
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        emit_cpi!(GameStatusChanged {
+            game: game_account.key(),
+            status: game_account.status,
+        });
+        Ok(())
+    }
+
+    pub fn begin_closing_game(ctx: Context<GameStatusAccounts>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        if game_account.status == GameStatus::Closing || game_account.status == GameStatus::Closed {
+            return Err(PokerError::InvalidGameStatus.into());
+        }
+        game_account.status = GameStatus::Closing;
+        game_account.close_if_empty();
+        game_account.last_activity = Clock::get()?.unix_timestamp;
+        emit_cpi!(GameStatusChanged {
+            game: game_account.key(),
+            status: game_account.status,
+        });
         Ok(())
     }
 
     pub fn close_game(ctx: Context<CloseGame>) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let game_token_account = &mut ctx.accounts.game_token_account;
-        let payer_token_account = &mut ctx.accounts.payer_token_account;
         let pda_account = &mut ctx.accounts.pda_account;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let owner = &ctx.accounts.owner;
         let payer = &ctx.accounts.payer;
-        if game_account.owner != payer.key() {
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&game_account.token_mint, mint)?;
+        let game_vault = holder(
+            mint,
+            &ctx.accounts.game_token_account,
+            pda_account.to_account_info(),
+        )?;
+        // anyone can tidy up an abandoned table, proceeds still go to the owner;
+        if !can_manage(
+            &game_account.owner,
+            &game_account.club,
+            &ctx.accounts.club,
+            &payer.key(),
+        ) && !game_account.is_abandoned(Clock::get()?.unix_timestamp)
+        {
             return Err(PokerError::NotGameOwner.into());
         }
-        if game_account.players.len() > 0 {
+        // waitlisted players need the game account to get their escrow back,
+        // see remove_from_waitlist;
+        if game_account.players.len() > 0 || !game_account.waitlist.is_empty() {
             return Err(PokerError::PlayersStillAtTable.into());
         }
-        let accounts = Transfer {
-            to: payer_token_account.to_account_info().clone(),
-            authority: pda_account.to_account_info().clone(),
-            from: game_token_account.to_account_info().clone(),
-        };
+        if !game_account.pending_settlements.is_empty() {
+            return Err(PokerError::SettlementsPending.into());
+        }
         let game_account_key = game_account.key();
         let seed = game_account_key.as_ref();
         let (_pda, _bump_seed) =
             Pubkey::find_program_address(&[game_account.key().as_ref()], ctx.program_id);
-        // this all the tokens left over from the game, basically the rake;
-        if game_token_account.amount > 0 {
-            transfer(
-                CpiContext::new_with_signer(
-                    token_program.to_account_info().clone(),
-                    accounts,
-                    &[&[seed, &[_bump_seed]]],
-                ),
-                game_token_account.amount,
+        let rake_paid = game_account.rake_accrued;
+        if game_account.rake_accrued > 0 {
+            transfer_tokens(
+                token_program,
+                system_program,
+                mint,
+                game_vault.clone(),
+                holder(
+                    mint,
+                    &ctx.accounts.rake_recipient_token_account,
+                    ctx.accounts.rake_recipient.to_account_info(),
+                )?,
+                pda_account.to_account_info(),
+                &[&[seed, &[_bump_seed]]],
+                game_account.rake_accrued,
+            )?;
+            game_account.rake_accrued = 0;
+        }
+        // anything left over is untracked dust, rake has already been paid out above;
+        let dust = vault_balance(mint, &game_vault)?;
+        if dust > 0 {
+            transfer_tokens(
+                token_program,
+                system_program,
+                mint,
+                game_vault.clone(),
+                holder(
+                    mint,
+                    &ctx.accounts.owner_token_account,
+                    owner.to_account_info(),
+                )?,
+                pda_account.to_account_info(),
+                &[&[seed, &[_bump_seed]]],
+                dust,
             )?;
         }
-        close_account(CpiContext::new_with_signer(
-            token_program.to_account_info(),
-            CloseAccount {
-                account: game_token_account.to_account_info(),
-                destination: payer.to_account_info(),
-                authority: pda_account.to_account_info(),
-            },
+        close_vault(
+            token_program,
+            system_program,
+            mint,
+            game_vault,
+            owner.to_account_info(),
+            pda_account.to_account_info(),
             &[&[seed, &[_bump_seed]]],
-        ))?;
+        )?;
+        emit_cpi!(GameClosed {
+            game: game_account.key(),
+            rake_paid,
+            dust,
+        });
+        Ok(())
+    }
+
+    pub fn create_club(
+        ctx: Context<CreateClubAccounts>,
+        name: String,
+        settings: ClubSettingsData,
+    ) -> Result<()> {
+        if name.len() > MAX_CLUB_NAME_LEN {
+            return Err(PokerError::ClubNameTooLong.into());
+        }
+        if settings.default_rake_bps > 10_000 {
+            return Err(PokerError::InvalidRake.into());
+        }
+        let club = &mut ctx.accounts.club;
+        club.owner = ctx.accounts.owner.key();
+        club.name = name;
+        club.admins = Vec::new();
+        club.default_rake_bps = settings.default_rake_bps;
+        club.default_rake_cap = settings.default_rake_cap;
+        club.default_rake_recipient = settings.default_rake_recipient;
+        club.default_token_mint = settings.default_token_mint;
+        club.allowlist = Vec::new();
+        Ok(())
+    }
+
+    /// Only affects tables and tournaments created from now on.
+    pub fn update_club_settings(
+        ctx: Context<ClubOwnerAccounts>,
+        settings: ClubSettingsData,
+    ) -> Result<()> {
+        if settings.default_rake_bps > 10_000 {
+            return Err(PokerError::InvalidRake.into());
+        }
+        let club = &mut ctx.accounts.club;
+        club.default_rake_bps = settings.default_rake_bps;
+        club.default_rake_cap = settings.default_rake_cap;
+        club.default_rake_recipient = settings.default_rake_recipient;
+        club.default_token_mint = settings.default_token_mint;
+        Ok(())
+    }
+
+    pub fn set_club_admins(ctx: Context<ClubOwnerAccounts>, data: ClubMembersData) -> Result<()> {
+        if data.members.len() > MAX_CLUB_ADMINS {
+            return Err(PokerError::TooManyClubAdmins.into());
+        }
+        ctx.accounts.club.admins = data.members;
+        Ok(())
+    }
+
+    pub fn set_club_allowlist(
+        ctx: Context<ClubAdminAccounts>,
+        data: ClubMembersData,
+    ) -> Result<()> {
+        if data.members.len() > MAX_CLUB_ALLOWLIST_LEN {
+            return Err(PokerError::ClubAllowlistFull.into());
+        }
+        ctx.accounts.club.allowlist = data.members;
+        Ok(())
+    }
+
+    /// Granting to a key that already holds a role on the scope replaces it.
+    pub fn grant_role(ctx: Context<GrantRoleAccounts>, grantee: Pubkey, role: Role) -> Result<()> {
+        let scope = &ctx.accounts.scope;
+        if scope_owner(scope, ctx.program_id)? != ctx.accounts.owner.key() {
+            return Err(PokerError::NotScopeOwner.into());
+        }
+        let role_account = &mut ctx.accounts.role_account;
+        role_account.scope = scope.key();
+        role_account.grantee = grantee;
+        role_account.role = role;
+        Ok(())
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRoleAccounts>) -> Result<()> {
+        if scope_owner(&ctx.accounts.scope, ctx.program_id)? != ctx.accounts.owner.key() {
+            return Err(PokerError::NotScopeOwner.into());
+        }
         Ok(())
     }
 
@@ -231,8 +1907,13 @@ pub mod degods_poker_program {
         let tournament_account = &mut ctx.accounts.tournament_account;
         let transactor = &ctx.accounts.transactor;
         let owner = &ctx.accounts.owner;
+        let club = &ctx.accounts.club;
+        let token_mint = resolve_token_mint(club, data.token_mint);
         tournament_account.owner = *ctx.accounts.owner.key;
-        tournament_account.token_mint = data.token_mint;
+        tournament_account.token_mint = token_mint;
+        if let Some(club) = club {
+            tournament_account.club = club.key();
+        }
         tournament_account.max_players = data.max_players;
         tournament_account.entry_cost = data.entry_cost;
         tournament_account.entry_fee = data.entry_fee;
@@ -243,27 +1924,56 @@ pub mod degods_poker_program {
         tournament_account.has_started = false;
         tournament_account.transactor = transactor.key();
         tournament_account.nft_payouts = Vec::from([]);
+        let host_registry = &mut ctx.accounts.host_registry;
+        host_registry.owner = owner.key();
+        host_registry.tournaments_created += 1;
         if tournament_account.payouts.len() > 1 {
             tournament_account.min_players = tournament_account.payouts.len() as u16;
         } else {
             tournament_account.min_players = 2;
         }
+        let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&token_mint, mint)?;
+        if is_native(&token_mint) {
+            fund_vault(
+                system_program,
+                owner.to_account_info(),
+                ctx.accounts.pda_account.to_account_info(),
+            )?;
+        }
         if data.guarantee != 0 {
-            tournament_account.guarantee = data.guarantee;
-            let owner_token_account = &mut ctx.accounts.owner_token_account;
-            let tournament_token_account = &mut ctx.accounts.tournament_token_account;
-            let token_program = &ctx.accounts.token_program;
-            let cpi_accounts = Transfer {
-                from: owner_token_account.to_account_info().clone(),
-                to: tournament_token_account.to_account_info().clone(),
-                authority: owner.to_account_info().clone(),
-            };
-            let cpi_program = token_program.to_account_info();
-            transfer(
-                CpiContext::new(cpi_program.clone(), cpi_accounts),
+            // the guarantee is whatever actually landed in the vault;
+            tournament_account.guarantee = deposit_tokens(
+                token_program,
+                system_program,
+                mint,
+                holder(
+                    mint,
+                    &ctx.accounts.owner_token_account,
+                    owner.to_account_info(),
+                )?,
+                holder(
+                    mint,
+                    &ctx.accounts.tournament_token_account,
+                    ctx.accounts.pda_account.to_account_info(),
+                )?,
+                owner.to_account_info(),
+                &[],
                 data.guarantee,
             )?;
         }
+        emit_cpi!(TournamentCreated {
+            tournament: tournament_account.key(),
+            owner: tournament_account.owner,
+            club: tournament_account.club,
+            token_mint,
+            max_players: tournament_account.max_players,
+            entry_cost: tournament_account.entry_cost,
+            entry_fee: tournament_account.entry_fee,
+            guarantee: tournament_account.guarantee,
+        });
         Ok(())
     }
 
@@ -276,13 +1986,16 @@ pub mod degods_poker_program {
         let tournament_nft_token_account = &mut ctx.accounts.tournament_nft_token_account;
         let owner_nft_token_account = &mut ctx.accounts.owner_nft_token_account;
         let token_program = &ctx.accounts.token_program;
-        let cpi_accounts = Transfer {
-            from: owner_nft_token_account.to_account_info().clone(),
-            to: tournament_nft_token_account.to_account_info().clone(),
-            authority: owner.to_account_info().clone(),
-        };
-        let cpi_program = token_program.to_account_info();
-        transfer(CpiContext::new(cpi_program.clone(), cpi_accounts), 1)?;
+        transfer_tokens(
+            token_program,
+            &ctx.accounts.system_program,
+            Some(&ctx.accounts.nft_mint),
+            owner_nft_token_account.to_account_info(),
+            tournament_nft_token_account.to_account_info(),
+            owner.to_account_info(),
+            &[],
+            1,
+        )?;
         tournament_account.nft_payouts.push(data.place_paid);
         if data.place_paid > tournament_account.min_players {
             tournament_account.min_players = data.place_paid;
@@ -295,17 +2008,30 @@ pub mod degods_poker_program {
         data: NftTournamentPrizeData,
     ) -> Result<()> {
         let tournament_account = &mut ctx.accounts.tournament_account;
-        let owner = &ctx.accounts.owner;
         let tournament_nft_token_account = &mut ctx.accounts.tournament_nft_token_account;
         let owner_nft_token_account = &mut ctx.accounts.owner_nft_token_account;
         let token_program = &ctx.accounts.token_program;
-        let cpi_accounts = Transfer {
-            from: owner_nft_token_account.to_account_info().clone(),
-            to: tournament_nft_token_account.to_account_info().clone(),
-            authority: owner.to_account_info().clone(),
-        };
-        let cpi_program = token_program.to_account_info();
-        transfer(CpiContext::new(cpi_program.clone(), cpi_accounts), 1)?;
+        let tournament_account_key = tournament_account.key();
+        let place = data.place_paid.to_le_bytes();
+        let (_pda, bump_seed) = Pubkey::find_program_address(
+            &[tournament_account_key.as_ref(), place.as_ref()],
+            ctx.program_id,
+        );
+        // the prize is held by the place's payout pda, and goes back to the owner;
+        transfer_tokens(
+            token_program,
+            &ctx.accounts.system_program,
+            Some(&ctx.accounts.nft_mint),
+            tournament_nft_token_account.to_account_info(),
+            owner_nft_token_account.to_account_info(),
+            ctx.accounts.tournament_nft_payout_account.to_account_info(),
+            &[&[
+                tournament_account_key.as_ref(),
+                place.as_ref(),
+                &[bump_seed],
+            ]],
+            1,
+        )?;
         let index = tournament_account
             .nft_payouts
             .iter()
@@ -337,94 +2063,144 @@ pub mod degods_poker_program {
     ) -> Result<()> {
         let tournament_account = &mut ctx.accounts.tournament_account;
         tournament_account.registration_open = !tournament_account.registration_open;
+        emit_cpi!(TournamentRegistrationChanged {
+            tournament: tournament_account.key(),
+            registration_open: tournament_account.registration_open,
+        });
         Ok(())
     }
 
     pub fn register_tournament(ctx: Context<JoinTournamentParams>) -> Result<()> {
         let tournament_account = &mut ctx.accounts.tournament_account;
-        let tournament_token_account = &mut ctx.accounts.tournament_token_account;
-        let player_token_account = &mut ctx.accounts.player_token_account;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let tournament_player_account = &mut ctx.accounts.tournament_player_account;
         let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&tournament_account.token_mint, mint)?;
         if tournament_account.players == tournament_account.max_players {
             return Err(PokerError::GameFull.into());
         }
+        check_allowlist(&tournament_account.club, &ctx.accounts.club, &player.key())?;
         tournament_account.players += 1;
         tournament_account.players_with_rebuys += 1;
-        let cpi_accounts = Transfer {
-            from: player_token_account.to_account_info().clone(),
-            to: tournament_token_account.to_account_info().clone(),
-            authority: player.to_account_info().clone(),
-        };
-        let cpi_program = token_program.to_account_info();
-
-        transfer(
-            CpiContext::new(cpi_program, cpi_accounts),
+        let received = deposit_tokens(
+            token_program,
+            system_program,
+            mint,
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            holder(
+                mint,
+                &ctx.accounts.tournament_token_account,
+                ctx.accounts.pda_account.to_account_info(),
+            )?,
+            player.to_account_info(),
+            &[],
             tournament_account.entry_cost + tournament_account.entry_fee,
         )?;
+        // transfer fees come out of the entry fee, the prize pool has to stay whole;
+        if received < tournament_account.entry_cost {
+            return Err(PokerError::NotEnoughFunds.into());
+        }
+        tournament_player_account.paid = received;
+        emit_cpi!(PlayerRegistered {
+            tournament: tournament_account.key(),
+            player: player.key(),
+            paid: received,
+        });
         Ok(())
     }
 
     pub fn unregister_tournament(ctx: Context<UnregisterTournamentParams>) -> Result<()> {
         let tournament_account = &mut ctx.accounts.tournament_account;
-        let tournament_token_account = &mut ctx.accounts.tournament_token_account;
-        let player_token_account = &mut ctx.accounts.player_token_account;
         let pda_account = &mut ctx.accounts.pda_account;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&tournament_account.token_mint, mint)?;
+        let tournament_vault = holder(
+            mint,
+            &ctx.accounts.tournament_token_account,
+            pda_account.to_account_info(),
+        )?;
         if tournament_account.has_started {
             return Err(PokerError::TournamentAlreadyStarted.into());
         }
         tournament_account.players -= 1;
         tournament_account.players_with_rebuys -= 1;
         let authority = &mut pda_account.to_account_info().clone();
-        let cpi_accounts = Transfer {
-            from: tournament_token_account.to_account_info().clone(),
-            to: player_token_account.to_account_info().clone(),
-            authority: authority.to_account_info().clone(),
-        };
-        let cpi_program = token_program.to_account_info();
         let tournament_account_key = tournament_account.key();
         let seed = tournament_account_key.as_ref();
         let (_pda, bump_seed) =
             Pubkey::find_program_address(&[tournament_account.key().as_ref()], ctx.program_id);
-        transfer(
-            CpiContext::new_with_signer(
-                cpi_program.clone(),
-                cpi_accounts,
-                &[&[seed, &[bump_seed]]],
-            ),
-            tournament_account.entry_cost + tournament_account.entry_fee,
+        // refund what the vault received, not the sticker price;
+        transfer_tokens(
+            token_program,
+            system_program,
+            mint,
+            tournament_vault,
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            authority.to_account_info(),
+            &[&[seed, &[bump_seed]]],
+            ctx.accounts.tournament_player_account.paid,
         )?;
+        emit_cpi!(PlayerUnregistered {
+            tournament: tournament_account.key(),
+            player: player.key(),
+            refunded: ctx.accounts.tournament_player_account.paid,
+        });
         Ok(())
     }
 
     pub fn refund_tournament(ctx: Context<RefundTournamentParams>) -> Result<()> {
         let tournament_account = &mut ctx.accounts.tournament_account;
-        let tournament_token_account = &mut ctx.accounts.tournament_token_account;
-        let player_token_account = &mut ctx.accounts.player_token_account;
         let pda_account = &mut ctx.accounts.pda_account;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&tournament_account.token_mint, mint)?;
+        let tournament_vault = holder(
+            mint,
+            &ctx.accounts.tournament_token_account,
+            pda_account.to_account_info(),
+        )?;
         tournament_account.players -= 1;
         tournament_account.players_with_rebuys -= 1;
         let authority = &mut pda_account.to_account_info().clone();
-        let cpi_accounts = Transfer {
-            from: tournament_token_account.to_account_info().clone(),
-            to: player_token_account.to_account_info().clone(),
-            authority: authority.to_account_info().clone(),
-        };
-        let cpi_program = token_program.to_account_info();
         let tournament_account_key = tournament_account.key();
         let seed = tournament_account_key.as_ref();
         let (_pda, bump_seed) =
             Pubkey::find_program_address(&[tournament_account.key().as_ref()], ctx.program_id);
-        transfer(
-            CpiContext::new_with_signer(
-                cpi_program.clone(),
-                cpi_accounts,
-                &[&[seed, &[bump_seed]]],
-            ),
-            tournament_account.entry_cost + tournament_account.entry_fee,
+        // refund what the vault received, not the sticker price;
+        transfer_tokens(
+            token_program,
+            system_program,
+            mint,
+            tournament_vault,
+            holder(
+                mint,
+                &ctx.accounts.player_token_account,
+                player.to_account_info(),
+            )?,
+            authority.to_account_info(),
+            &[&[seed, &[bump_seed]]],
+            ctx.accounts.tournament_player_account.paid,
         )?;
+        emit_cpi!(PlayerUnregistered {
+            tournament: tournament_account.key(),
+            player: player.key(),
+            refunded: ctx.accounts.tournament_player_account.paid,
+        });
         Ok(())
     }
 
@@ -437,19 +2213,32 @@ pub mod degods_poker_program {
             return Err(PokerError::NotEnoughPlayersToStartTournament.into());
         }
         tournament_account.has_started = true;
+        emit_cpi!(TournamentStarted {
+            tournament: tournament_account.key(),
+            players: tournament_account.players,
+        });
         Ok(())
     }
 
     pub fn payout_tournament_player(ctx: Context<BustTournamentParams>) -> Result<()> {
         let tournament_account = &mut ctx.accounts.tournament_account;
-        let tournament_token_account = &mut ctx.accounts.tournament_token_account;
-        let player_token_account = &mut ctx.accounts.player_token_account;
         let pda_account = &mut ctx.accounts.pda_account;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let player = &ctx.accounts.player;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&tournament_account.token_mint, mint)?;
+        let tournament_vault = holder(
+            mint,
+            &ctx.accounts.tournament_token_account,
+            pda_account.to_account_info(),
+        )?;
         if !tournament_account.has_started {
             return Err(PokerError::TournamentNotStarted.into());
         }
         // technically you could bust before registration closes and be in the money.  We should just not do that...;
+        let position = tournament_account.players;
+        let mut payout = 0;
         if !tournament_account.registration_open
             // if the number of payouts we send out if great than or equal to the players left, the guy who busted is in the money;
             && tournament_account.payouts.len() >= tournament_account.players as usize
@@ -466,36 +2255,49 @@ pub mod degods_poker_program {
                 * total_payout)
                 / 1000;
             let authority = &mut pda_account.to_account_info().clone();
-            let cpi_accounts = Transfer {
-                from: tournament_token_account.to_account_info().clone(),
-                to: player_token_account.to_account_info().clone(),
-                authority: authority.to_account_info().clone(),
-            };
-            let cpi_program = token_program.to_account_info();
             let tournament_account_key = tournament_account.key();
             let seed = tournament_account_key.as_ref();
             let (_pda, bump_seed) =
                 Pubkey::find_program_address(&[tournament_account.key().as_ref()], ctx.program_id);
-            transfer(
-                CpiContext::new_with_signer(
-                    cpi_program.clone(),
-                    cpi_accounts,
-                    &[&[seed, &[bump_seed]]],
-                ),
+            transfer_tokens(
+                token_program,
+                system_program,
+                mint,
+                tournament_vault,
+                holder(
+                    mint,
+                    &ctx.accounts.player_token_account,
+                    player.to_account_info(),
+                )?,
+                authority.to_account_info(),
+                &[&[seed, &[bump_seed]]],
                 current_payout,
             )?;
+            payout = current_payout;
         }
         tournament_account.players -= 1;
+        emit_cpi!(PlayerBusted {
+            tournament: tournament_account.key(),
+            player: player.key(),
+            position,
+            payout,
+        });
         Ok(())
     }
 
     pub fn close_tournament(ctx: Context<CloseTournamentParams>) -> Result<()> {
         let tournament_account = &mut ctx.accounts.tournament_account;
-        let tournament_token_account = &mut ctx.accounts.tournament_token_account;
         let pda_account = &mut ctx.accounts.pda_account;
-        let owner_token_account = &mut ctx.accounts.owner_token_account;
         let owner = &ctx.accounts.owner;
         let token_program = &ctx.accounts.token_program;
+        let system_program = &ctx.accounts.system_program;
+        let mint = ctx.accounts.mint.as_ref();
+        check_mint(&tournament_account.token_mint, mint)?;
+        let tournament_vault = holder(
+            mint,
+            &ctx.accounts.tournament_token_account,
+            pda_account.to_account_info(),
+        )?;
 
         if tournament_account.players != 0 {
             return Err(PokerError::PlayersStillAtTable.into());
@@ -505,37 +2307,42 @@ pub mod degods_poker_program {
             return Err(PokerError::NFTsEscrowedInTournament.into());
         }
 
-        let cpi_program = token_program.to_account_info();
         let tournament_account_key = tournament_account.key();
         let seed = tournament_account_key.as_ref();
         let (_pda, bump_seed) =
             Pubkey::find_program_address(&[tournament_account.key().as_ref()], ctx.program_id);
         let authority = &mut pda_account.to_account_info().clone();
-        let owner_cpi_accounts = Transfer {
-            from: tournament_token_account.to_account_info().clone(),
-            to: owner_token_account.to_account_info().clone(),
-            authority: authority.to_account_info().clone(),
-        };
-        transfer(
-            CpiContext::new_with_signer(
-                cpi_program.clone(),
-                owner_cpi_accounts,
-                &[&[seed, &[bump_seed]]],
-            ),
+        let remainder = vault_balance(mint, &tournament_vault)?;
+        transfer_tokens(
+            token_program,
+            system_program,
+            mint,
+            tournament_vault.clone(),
+            holder(
+                mint,
+                &ctx.accounts.owner_token_account,
+                owner.to_account_info(),
+            )?,
+            authority.to_account_info(),
+            &[&[seed, &[bump_seed]]],
             // there could be left over guarantee here?;
-            tournament_token_account.amount,
+            remainder,
         )?;
         let (_pda, _bump_seed) =
             Pubkey::find_program_address(&[tournament_account.key().as_ref()], ctx.program_id);
-        close_account(CpiContext::new_with_signer(
-            token_program.to_account_info(),
-            CloseAccount {
-                account: tournament_token_account.to_account_info(),
-                destination: owner.to_account_info(),
-                authority: authority.to_account_info(),
-            },
+        close_vault(
+            token_program,
+            system_program,
+            mint,
+            tournament_vault,
+            owner.to_account_info(),
+            authority.to_account_info(),
             &[&[seed, &[_bump_seed]]],
-        ))?;
+        )?;
+        emit_cpi!(TournamentClosed {
+            tournament: tournament_account.key(),
+            remainder,
+        });
         Ok(())
     }
 }
@@ -0,0 +1,85 @@
+//! Deck shuffling shared by the program and its clients. Everything here is
+//! deterministic, so anyone holding the revealed seeds of a hand can rebuild
+//! the exact deck order the dealer was bound to.
+
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::state::GameVariant;
+
+/// What a party publishes before the hand: `sha256(seed)`.
+pub fn commitment(seed: &[u8; 32]) -> [u8; 32] {
+    hashv(&[seed]).to_bytes()
+}
+
+/// What the dealer publishes for each card it deals: `sha256([card] || salt)`.
+pub fn card_commitment(card: u8, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&[card], salt]).to_bytes()
+}
+
+/// Combines every revealed seed, in commit order, into the deck seed.
+pub fn deck_seed(seeds: &[[u8; 32]]) -> [u8; 32] {
+    let parts: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_ref()).collect();
+    hashv(&parts).to_bytes()
+}
+
+/// Cards in the deck for a variant; short deck drops the twos through fives.
+pub fn deck_size(variant: GameVariant) -> usize {
+    match variant {
+        GameVariant::ShortDeck => 36,
+        _ => 52,
+    }
+}
+
+/// Fisher-Yates shuffle of `0..deck_size` driven by `deck_seed`. Card `c`
+/// is rank `c / 4` and suit `c % 4`, ranks counted up from the deck's lowest.
+pub fn permutation(deck_seed: &[u8; 32], deck_size: usize) -> Vec<u8> {
+    let mut deck: Vec<u8> = (0..deck_size as u8).collect();
+    let mut stream = SeedStream::new(deck_seed);
+    for i in (1..deck_size).rev() {
+        let j = stream.below(i as u64 + 1) as usize;
+        deck.swap(i, j);
+    }
+    deck
+}
+
+/// Endless u64s from `sha256(deck_seed || counter)`, four per block.
+struct SeedStream {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    offset: usize,
+}
+
+impl SeedStream {
+    fn new(seed: &[u8; 32]) -> Self {
+        SeedStream {
+            seed: *seed,
+            counter: 0,
+            block: [0; 32],
+            offset: 32,
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if self.offset == 32 {
+            self.block = hashv(&[&self.seed, &self.counter.to_le_bytes()]).to_bytes();
+            self.counter += 1;
+            self.offset = 0;
+        }
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.block[self.offset..self.offset + 8]);
+        self.offset += 8;
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Uniform in `0..bound`; rejects the top sliver of draws to avoid modulo bias.
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let draw = self.next_u64();
+            if draw < zone {
+                return draw % bound;
+            }
+        }
+    }
+}
@@ -1,7 +1,255 @@
 use std::collections::{HashSet, BTreeSet};
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::PokerError;
+use crate::holdem::Outcome;
+
+// Host Registry
+
+/// Per-owner counters; games and tournaments live at PDAs derived from the
+/// owner and their index, so a wallet's tables can be listed from this alone.
+#[account]
+pub struct HostRegistry {
+    pub owner: Pubkey,            // 32
+    pub games_created: u64,       // 8
+    pub tournaments_created: u64, // 8
+}
+
+// Club
+
+/// Groups tables and tournaments under one organisation. Admins can manage
+/// anything created under the club, and its defaults fill in whatever the
+/// create instruction leaves unset.
+#[account]
+pub struct Club {
+    pub owner: Pubkey,                  // 32
+    pub name: String,                   // 4 + 32
+    pub admins: Vec<Pubkey>,            // 4 + 32 * 10
+    pub default_rake_bps: u16,          // 2
+    pub default_rake_cap: u64,          // 8
+    pub default_rake_recipient: Pubkey, // 32
+    pub default_token_mint: Pubkey,     // 32
+    pub allowlist: Vec<Pubkey>,         // 4 + 32 * 50
+}
+
+pub const MAX_CLUB_NAME_LEN: usize = 32;
+pub const MAX_CLUB_ADMINS: usize = 10;
+pub const MAX_CLUB_ALLOWLIST_LEN: usize = 50;
+
+impl Club {
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        *key == self.owner || self.admins.contains(key)
+    }
+
+    /// An empty allowlist leaves the club's tables open to everyone.
+    pub fn allows(&self, key: &Pubkey) -> bool {
+        self.allowlist.is_empty() || self.allowlist.contains(key)
+    }
+}
+
+/// Whether `key` may manage a game or tournament: its owner, or an admin of
+/// the club it was created under. `club` is whatever the caller passed in.
+pub fn can_manage(
+    owner: &Pubkey,
+    club_key: &Pubkey,
+    club: &Option<Account<Club>>,
+    key: &Pubkey,
+) -> bool {
+    if key == owner {
+        return true;
+    }
+    match club {
+        Some(club) => *club_key != Pubkey::default() && club.key() == *club_key && club.is_admin(key),
+        None => false,
+    }
+}
+
+/// Club tables and tournaments only take players on the club's allowlist.
+pub fn check_allowlist(
+    club_key: &Pubkey,
+    club: &Option<Account<Club>>,
+    player: &Pubkey,
+) -> Result<()> {
+    if *club_key == Pubkey::default() {
+        return Ok(());
+    }
+    match club {
+        Some(club) if club.key() == *club_key => {
+            if !club.allows(player) {
+                return Err(PokerError::NotOnAllowlist.into());
+            }
+            Ok(())
+        }
+        _ => Err(PokerError::ClubRequired.into()),
+    }
+}
+
+/// The token mint a new game or tournament uses, falling back to the club's
+/// default when none was given.
+pub fn resolve_token_mint(club: &Option<Account<Club>>, token_mint: Pubkey) -> Pubkey {
+    match club {
+        Some(club) if token_mint == Pubkey::default() => club.default_token_mint,
+        _ => token_mint,
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct ClubSettingsData {
+    pub default_rake_bps: u16,
+    pub default_rake_cap: u64,
+    pub default_rake_recipient: Pubkey,
+    pub default_token_mint: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateClubAccounts<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + 32 + 4 + 32 + 4 + 32 * 10 + 2 + 8 + 32 + 32 + 4 + 32 * 50,
+        seeds = [b"club", owner.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub club: Account<'info, Club>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClubOwnerAccounts<'info> {
+    #[account(mut, constraint = club.owner == owner.key())]
+    pub club: Account<'info, Club>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClubAdminAccounts<'info> {
+    #[account(mut, constraint = club.is_admin(&admin.key()))]
+    pub club: Account<'info, Club>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct ClubMembersData {
+    pub members: Vec<Pubkey>,
+}
+
+// Roles
+
+/// A key the owner has delegated part of a game, tournament or club to. A
+/// role granted on a club applies to every table and tournament under it.
+/// The owner itself never needs one and keeps every permission.
+#[account]
+pub struct RoleAccount {
+    pub scope: Pubkey,   // 32
+    pub grantee: Pubkey, // 32
+    pub role: Role,      // 1
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Changes configuration: transactor, status, payouts, registration.
+    Admin,
+    /// Runs hands: starts and settles them, takes rake, ejects and busts players.
+    Dealer,
+    /// Moves money out on someone's behalf: refunds and rake withdrawals.
+    Cashier,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    Configure,
+    Deal,
+    Pay,
+}
+
+impl Role {
+    pub fn allows(&self, permission: Permission) -> bool {
+        matches!(
+            (self, permission),
+            (Role::Admin, Permission::Configure)
+                | (Role::Dealer, Permission::Deal)
+                | (Role::Cashier, Permission::Pay)
+        )
+    }
+}
+
+/// Whether `key` holds a role carrying `permission` on `scope` or on the club
+/// `scope` was created under. `role` is whatever the caller passed in.
+pub fn has_role(
+    role: &Option<Account<RoleAccount>>,
+    scope: &Pubkey,
+    club_key: &Pubkey,
+    key: &Pubkey,
+    permission: Permission,
+) -> bool {
+    match role {
+        Some(role) => {
+            role.grantee == *key
+                && (role.scope == *scope
+                    || (*club_key != Pubkey::default() && role.scope == *club_key))
+                && role.role.allows(permission)
+        }
+        None => false,
+    }
+}
+
+/// The owner of a game, tournament or club, whichever `scope` turns out to be.
+pub fn scope_owner(scope: &AccountInfo, program_id: &Pubkey) -> Result<Pubkey> {
+    if scope.owner != program_id {
+        return Err(PokerError::InvalidRoleScope.into());
+    }
+    let data = scope.try_borrow_data()?;
+    if let Ok(game) = GameAccount::try_deserialize(&mut &data[..]) {
+        return Ok(game.owner);
+    }
+    if let Ok(tournament) = TournamentAccount::try_deserialize(&mut &data[..]) {
+        return Ok(tournament.owner);
+    }
+    if let Ok(club) = Club::try_deserialize(&mut &data[..]) {
+        return Ok(club.owner);
+    }
+    Err(PokerError::InvalidRoleScope.into())
+}
+
+#[derive(Accounts)]
+#[instruction(grantee: Pubkey)]
+pub struct GrantRoleAccounts<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 32 + 1,
+        seeds = [b"role", scope.key().as_ref(), grantee.as_ref()],
+        bump
+    )]
+    pub role_account: Account<'info, RoleAccount>,
+    /// CHECK: a game, tournament or club, checked in the handler;
+    pub scope: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRoleAccounts<'info> {
+    #[account(
+        mut,
+        seeds = [b"role", scope.key().as_ref(), role_account.grantee.as_ref()],
+        bump,
+        close = owner
+    )]
+    pub role_account: Account<'info, RoleAccount>,
+    /// CHECK: a game, tournament or club, checked in the handler;
+    pub scope: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
 
 // Create Game
 
@@ -11,193 +259,1239 @@ pub struct CreateGameData {
     pub min_deposit: u64,
     pub max_deposit: u64,
     pub token_mint: Pubkey,
+    pub rake_bps: u16,
+    pub rake_cap: u64,
+    pub rake_recipient: Pubkey,
+    pub inactivity_timeout: i64,
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: u64,
+    pub variant: GameVariant,
+    pub table_name: String,
+    // when non-zero these override min_deposit/max_deposit as multiples of the big blind;
+    pub min_buy_in_bb: u16,
+    pub max_buy_in_bb: u16,
+    pub max_sit_out_seconds: i64,
+    pub mode: GameMode,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateGameParams<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 8 + 8,
+        seeds = [b"host", payer.key().as_ref()],
+        bump
+    )]
+    pub host_registry: Account<'info, HostRegistry>,
+    #[account(
+        init,
+        payer = payer,
+        space =  32 + 2 + 8 + 8 + 2 + 8 + (4 + 74 * MAX_SEATS) + 2 + 32 + 2 + 8 + 32 + 8 + 32 + 1 + 8 + 8 + 1
+            + 8 + 8 + 8 + 1 + 36 + 2 + 2 + 324 + 8 + 32 + 32 + 8 + 9 + 8 + 32 + 1054 + 1 + 8,
+        seeds = [
+            b"game",
+            payer.key().as_ref(),
+            host_registry.games_created.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    /// CHECK: pda account has no state;
+    #[account(mut, seeds = [
+        game_account.key().as_ref()
+    ], bump)]
+    pub pda_account: UncheckedAccount<'info>,
+    #[account(constraint = club.is_admin(&payer.key()) @ PokerError::NotClubAdmin)]
+    pub club: Option<Account<'info, Club>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub transactor: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct GameAccount {
+    pub owner: Pubkey,                   // 32
+    pub max_players: u16,                // 2
+    pub min_deposit: u64,                // 8
+    pub max_deposit: u64,                // 8
+    pub players: Box<Vec<SeatedPlayer>>, // 4 + 74 * MAX_SEATS;
+    pub token_mint: Pubkey,              // 32
+    pub rake_bps: u16,                   // 2
+    pub rake_cap: u64,                   // 8
+    pub rake_recipient: Pubkey,          // 32
+    pub rake_accrued: u64,               // 8
+    pub transactor: Pubkey,              // 32
+    pub hand_in_progress: bool,          // 1
+    pub last_activity: i64,              // 8
+    pub inactivity_timeout: i64,         // 8
+    pub status: GameStatus,              // 1
+    pub small_blind: u64,                // 8
+    pub big_blind: u64,                  // 8
+    pub ante: u64,                       // 8
+    pub variant: GameVariant,            // 1
+    pub table_name: String,              // 4 + 32
+    pub min_buy_in_bb: u16,              // 2
+    pub max_buy_in_bb: u16,              // 2
+    pub waitlist: Vec<Pubkey>,           // 4 + 32 * 10
+    pub max_sit_out_seconds: i64,        // 8
+    pub club: Pubkey,                    // 32, default when not under a club
+    pub hand_history: [u8; 32],          // 32
+    pub hands_committed: u64,            // 8
+    pub mode: GameMode,                  // 9
+    pub challenge_window: i64,           // 8, zero pays ejected players straight away
+    pub arbiter: Pubkey,                 // 32
+    pub pending_settlements: Vec<PendingSettlement>, // 4 + 105 * 10
+    pub signed_cash_outs: bool,          // 1, players only leave on a signed balance statement
+    pub statement_nonce: u64,            // 8, highest statement nonce paid out
+}
+
+pub const MAX_TABLE_NAME_LEN: usize = 32;
+/// Most seats a table can have; `players` is sized for this many.
+pub const MAX_SEATS: usize = 10;
+pub const MAX_WAITLIST_LEN: usize = 10;
+pub const MAX_PENDING_SETTLEMENTS: usize = 10;
+
+/// Seconds the arbiter gets to rule on a dispute before the payout can be
+/// released as the operator reported it.
+pub const ARBITER_TIMEOUT: i64 = 7 * 24 * 60 * 60;
+
+/// An ejected player's payout, held in the vault while the table's challenge
+/// window runs and, if the player disputes it, until the arbiter rules.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
+pub struct PendingSettlement {
+    pub player: Pubkey,     // 32
+    /// What the operator reported.
+    pub amount: u64,        // 8
+    /// What was taken off the seat for this payout, the most a ruling can pay.
+    pub held: u64,          // 8
+    /// When the payout can be released; once disputed, the arbiter's deadline.
+    pub release_at: i64,    // 8
+    pub disputed: bool,     // 1
+    /// What the player says they're owed, and the hash of their signed record of the hand.
+    pub claimed: u64,       // 8
+    pub evidence: [u8; 32], // 32
+    /// A balance statement needs a higher nonce to replace this payout.
+    pub nonce: u64,         // 8
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameVariant {
+    #[default]
+    Nlhe,
+    Plo,
+    Plo5,
+    ShortDeck,
+}
+
+/// Who runs the hands at a table.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameMode {
+    /// A trusted dealer plays hands off-chain and reports them via `settle_hand`.
+    #[default]
+    Dealer,
+    /// Heads-up No-Limit Hold'em played out on-chain, see `holdem`. A player
+    /// who hasn't acted within `action_timeout` seconds can be timed out.
+    HeadsUp { action_timeout: i64 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameStatus {
+    /// Accepting joins and top ups.
+    Open,
+    /// Frozen by the owner; seated players keep playing but nobody new sits down.
+    Paused,
+    /// Winding down; becomes `Closed` once the last player is gone.
+    Closing,
+    Closed,
+}
+
+impl GameAccount {
+    /// The owner or the transactor, i.e. anyone allowed to run the table day to day.
+    pub fn is_operator(&self, key: &Pubkey) -> bool {
+        *key == self.owner || *key == self.transactor
+    }
+
+    /// True once neither the owner nor the transactor has touched the table
+    /// for `inactivity_timeout` seconds, after which players can withdraw
+    /// without them.
+    pub fn is_abandoned(&self, now: i64) -> bool {
+        now - self.last_activity >= self.inactivity_timeout
+    }
+
+    /// Lowest seat number nobody is sitting in, if the table isn't full.
+    pub fn lowest_free_seat(&self) -> Option<u8> {
+        (0..self.max_players.min(u8::MAX as u16) as u8)
+            .find(|seat| self.players.iter().all(|p| p.seat != *seat))
+    }
+
+    /// Moves a `Closing` table to `Closed` once the last seat has been vacated.
+    pub fn close_if_empty(&mut self) {
+        if self.status == GameStatus::Closing && self.players.is_empty() {
+            self.status = GameStatus::Closed;
+        }
+    }
+
+    /// Sum of every seat's current balance, i.e. what the vault owes the table.
+    pub fn seat_balances(&self) -> Result<u64> {
+        match self.players.iter().try_fold(0u64, |sum, p| sum.checked_add(p.balance)) {
+            Some(sum) => Ok(sum),
+            None => Err(PokerError::AmountOverflow.into()),
+        }
+    }
+
+    /// Held back for ejected players whose payouts haven't been released yet.
+    pub fn pending_held(&self) -> Result<u64> {
+        match self.pending_settlements.iter().try_fold(0u64, |sum, s| sum.checked_add(s.held)) {
+            Some(sum) => Ok(sum),
+            None => Err(PokerError::AmountOverflow.into()),
+        }
+    }
+
+    /// Holds an ejected player's payout until `release_at`. A player ejected
+    /// again before being paid has the two folded together.
+    pub fn queue_settlement(
+        &mut self,
+        player: Pubkey,
+        amount: u64,
+        held: u64,
+        release_at: i64,
+        nonce: u64,
+    ) -> Result<()> {
+        if let Some(pending) = self.pending_settlements.iter_mut().find(|s| s.player == player) {
+            pending.amount = match pending.amount.checked_add(amount) {
+                Some(amount) => amount,
+                None => return Err(PokerError::AmountOverflow.into()),
+            };
+            pending.held = match pending.held.checked_add(held) {
+                Some(held) => held,
+                None => return Err(PokerError::AmountOverflow.into()),
+            };
+            pending.release_at = release_at;
+            pending.nonce = pending.nonce.max(nonce);
+            return Ok(());
+        }
+        if self.pending_settlements.len() >= MAX_PENDING_SETTLEMENTS {
+            return Err(PokerError::TooManyPendingSettlements.into());
+        }
+        self.pending_settlements.push(PendingSettlement {
+            player,
+            amount,
+            held,
+            release_at,
+            nonce,
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Hands back what was held for a payout but not paid: onto the player's
+    /// seat if they're still at the table, otherwise as an undisputed payout
+    /// they can release straight away.
+    pub fn return_held(&mut self, player: Pubkey, amount: u64, nonce: u64, now: i64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        if let Some(seat) = self.players.iter_mut().find(|p| p.address == player) {
+            seat.balance = match seat.balance.checked_add(amount) {
+                Some(balance) => balance,
+                None => return Err(PokerError::AmountOverflow.into()),
+            };
+            seat.withdrawn = seat.withdrawn.saturating_sub(amount);
+            return Ok(());
+        }
+        self.queue_settlement(player, amount, amount, now, nonce)
+    }
+
+    /// Fund-conservation invariant: the vault must always hold at least what
+    /// the seats are owed, plus pending payouts and the rake collected but
+    /// not yet withdrawn. Checked after every payout out of the vault.
+    pub fn check_vault_covers_seats(&self, vault_amount: u64) -> Result<()> {
+        let owed = match self
+            .seat_balances()?
+            .checked_add(self.pending_held()?)
+            .and_then(|owed| owed.checked_add(self.rake_accrued))
+        {
+            Some(owed) => owed,
+            None => return Err(PokerError::AmountOverflow.into()),
+        };
+        if vault_amount < owed {
+            return Err(PokerError::VaultUnderfunded.into());
+        }
+        Ok(())
+    }
+
+    /// Seconds a heads-up player has to act; dealer-run tables have no clock.
+    pub fn action_timeout(&self) -> Result<i64> {
+        match self.mode {
+            GameMode::HeadsUp { action_timeout } => Ok(action_timeout),
+            GameMode::Dealer => Err(PokerError::InvalidGameMode.into()),
+        }
+    }
+
+    /// On-chain hands keep their chips in the seats until they finish, so
+    /// nothing may be paid out of a seat while one is running.
+    pub fn check_seats_unlocked(&self) -> Result<()> {
+        if self.hand_in_progress && self.mode != GameMode::Dealer {
+            return Err(PokerError::HandInProgress.into());
+        }
+        Ok(())
+    }
+
+    /// Rake owed on a pot: `rake_bps` of the pot, capped at `rake_cap` per hand.
+    pub fn max_rake(&self, pot: u64) -> u64 {
+        let rake = (pot as u128 * self.rake_bps as u128 / 10_000) as u64;
+        rake.min(self.rake_cap)
+    }
+
+    /// Chains a hand's history hash onto the table's running commitment,
+    /// `hand_history = sha256(hand_history || hand_hash)`. Replaying the hand
+    /// logs in order from all zeroes must land on the stored value.
+    pub fn commit_hand(&mut self, hand_hash: &[u8; 32]) {
+        self.hand_history = hashv(&[&self.hand_history, hand_hash]).to_bytes();
+        self.hands_committed += 1;
+    }
+}
+
+// Join Game
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct JoinGame<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        mut, 
+        constraint = game_token_account.owner == pda_account.key(), 
+        constraint = game_token_account.mint == game_account.token_mint
+    )]
+    pub game_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut, 
+        constraint = player_token_account.owner == player.key(),
+        constraint = player_token_account.mint == game_account.token_mint
+    )]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+    /// CHECK: pda account has no state;
+    #[account(mut, seeds = [
+        game_account.key().as_ref()
+    ], bump)]
+    pub pda_account: UncheckedAccount<'info>,
+    pub club: Option<Account<'info, Club>>,
+    #[account(mut, constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct JoinGameArgs {
+    pub amount: u64,
+    pub seat: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
+pub struct SeatedPlayer {
+    pub address: Pubkey, // 32
+    pub deposited: u64,  // 8
+    pub withdrawn: u64,  // 8
+    pub balance: u64,    // 8
+    pub seat: u8,        // 1
+    pub sitting_out: bool, // 1
+    pub sat_out_at: i64, // 8
+    /// Balance statements for this seat need a higher nonce; starts at the
+    /// table's `statement_nonce` so statements from an earlier sitting can't be replayed.
+    pub nonce: u64,      // 8
+}
+
+// Sit Out
+#[derive(Accounts)]
+pub struct SitOutAccounts<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    pub player: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct SitOutData {
+    pub sitting_out: bool,
+}
+
+// Add Chips
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AddChipsData {
+    pub amount: u64,
+    // ignore amount and top the stack up to exactly max_deposit;
+    pub top_up_to_max: bool,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddChips<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        mut, 
+        constraint = game_token_account.owner == pda_account.key(),
+        constraint = game_token_account.mint == game_account.token_mint
+    )]
+    pub game_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut, 
+        constraint = player_token_account.owner == player.key(),
+        constraint = player_token_account.mint == game_account.token_mint
+    )]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+    /// CHECK: pda account has no state;
+    #[account(mut, seeds = [
+        game_account.key().as_ref()
+    ], bump)]
+    pub pda_account: UncheckedAccount<'info>,
+    #[account(constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Eject Player
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EjectPlayersAccounts<'info> {
+    #[account(
+        mut, 
+        constraint = game_account.is_operator(&payer.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &payer.key(), Permission::Deal)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        mut, 
+        constraint = token_account.owner == pda_account.key(),
+        constraint = game_account.token_mint == token_account.mint
+    )]
+    pub token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
+    /// CHECK: pda account has no state;
+    #[account(mut, seeds = [
+        game_account.key().as_ref()
+    ], bump)]
+    pub pda_account: UncheckedAccount<'info>,
+    #[account(mut, constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct EjectPlayersParams {
+    pub amounts: Vec<u64>,
+}
+
+
+// Eject Sat Out Players; same accounts as eject, payouts are each seat's balance;
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EjectSatOutPlayersAccounts<'info> {
+    #[account(
+        mut,
+        constraint = game_account.is_operator(&payer.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &payer.key(), Permission::Deal)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        mut,
+        constraint = token_account.owner == pda_account.key(),
+        constraint = game_account.token_mint == token_account.mint
+    )]
+    pub token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
+    /// CHECK: pda account has no state;
+    #[account(mut, seeds = [
+        game_account.key().as_ref()
+    ], bump)]
+    pub pda_account: UncheckedAccount<'info>,
+    #[account(mut, constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Refund Player
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefundPlayerAccounts<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        mut, 
+        constraint = game_token_account.owner == pda_account.key(),
+        constraint = game_token_account.mint == game_account.token_mint
+    )]
+    pub game_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut, 
+        constraint = player_token_account.owner == player.key(),
+        constraint = player_token_account.mint == game_account.token_mint
+    )]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: only used to look up the seat and receive native SOL refunds;
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
+    /// CHECK: pda account has no state;
+    #[account(mut, seeds = [
+        game_account.key().as_ref()
+    ], bump)]
+    pub pda_account: UncheckedAccount<'info>,
+    #[account(constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct RefundPlayerParams {
+    pub amount: u64,
+}
+
+
+
+// Leave Table
+#[event_cpi]
+#[derive(Accounts)]
+pub struct LeaveTableAccounts<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        mut,
+        constraint = game_token_account.owner == pda_account.key(),
+        constraint = game_token_account.mint == game_account.token_mint
+    )]
+    pub game_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key(),
+        constraint = player_token_account.mint == game_account.token_mint
+    )]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+    /// CHECK: pda account has no state;
+    #[account(mut, seeds = [
+        game_account.key().as_ref()
+    ], bump)]
+    pub pda_account: UncheckedAccount<'info>,
+    #[account(mut, constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The heads-up hand an abandoned table was left in the middle of.
+    #[account(mut, seeds = [b"hand", game_account.key().as_ref()], bump)]
+    pub hand_account: Option<Box<Account<'info, HandAccount>>>,
+}
+
+// Start Hand
+#[derive(Accounts)]
+pub struct StartHandAccounts<'info> {
+    #[account(
+        mut,
+        constraint = game_account.is_operator(&payer.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &payer.key(), Permission::Deal)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
+    /// Every hand is dealt from a committed round, which this locks.
+    #[account(mut, seeds = [b"shuffle", game_account.key().as_ref()], bump)]
+    pub shuffle_account: Account<'info, ShuffleAccount>,
+}
+
+// Settle Hand
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleHandAccounts<'info> {
+    #[account(
+        mut,
+        constraint = game_account.is_operator(&payer.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &payer.key(), Permission::Deal)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct SeatDelta {
+    pub player: Pubkey,
+    pub delta: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct SettleHandData {
+    pub deltas: Vec<SeatDelta>,
+    pub rake: u64,
+    /// The hand's history hash, if it's committed along with the settlement.
+    pub hand_hash: Option<[u8; 32]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct CommitHandData {
+    pub hand_hash: [u8; 32],
+}
+
+// Shuffle
+
+/// One hand's commit-reveal round. The dealer opens it with their commitment,
+/// every seated player adds theirs, `start_hand` locks it, and once the hand
+/// is over each party reveals. The last reveal fixes `deck_seed`, from which
+/// `shuffle::permutation` rebuilds the deck. The first reveal starts the clock
+/// on everyone else; the next round can't be opened until all seeds are in or
+/// `reveal_deadline` has passed.
+#[account]
+pub struct ShuffleAccount {
+    pub game: Pubkey,               // 32
+    pub hand: u64,                  // 8
+    pub phase: ShufflePhase,        // 1
+    pub parties: Vec<ShuffleParty>, // 4 + 97 * MAX_SHUFFLE_PARTIES, the dealer plus every seat;
+    pub deck_seed: [u8; 32],        // 32
+    pub reveal_deadline: i64,       // 8, zero until the first reveal;
+}
+
+pub const MAX_SHUFFLE_PARTIES: usize = MAX_SEATS + 1;
+
+/// Seconds the other parties get to reveal once the first seed is out.
+pub const SHUFFLE_REVEAL_TIMEOUT: i64 = 10 * 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ShuffleParty {
+    pub party: Pubkey,        // 32
+    pub commitment: [u8; 32], // 32
+    pub seed: [u8; 32],       // 32
+    pub revealed: bool,       // 1
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShufflePhase {
+    /// Taking commitments.
+    Committing,
+    /// The hand has been dealt; commitments are frozen and reveals are open.
+    Dealt,
+    /// Every seed is in and `deck_seed` is final.
+    Revealed,
+}
+
+impl ShuffleAccount {
+    /// Whether anyone seated and not sitting out has yet to commit.
+    pub fn missing_commitments(&self, game: &GameAccount) -> bool {
+        game.players
+            .iter()
+            .filter(|p| !p.sitting_out)
+            .any(|p| self.parties.iter().all(|c| c.party != p.address))
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct OpenShuffleAccounts<'info> {
+    #[account(
+        mut,
+        constraint = game_account.is_operator(&payer.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &payer.key(), Permission::Deal)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 8 + 1 + 4 + 97 * MAX_SHUFFLE_PARTIES + 32 + 8,
+        seeds = [b"shuffle", game_account.key().as_ref()],
+        bump
+    )]
+    pub shuffle_account: Account<'info, ShuffleAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ShuffleSeedAccounts<'info> {
+    pub game_account: Account<'info, GameAccount>,
+    #[account(mut, seeds = [b"shuffle", game_account.key().as_ref()], bump)]
+    pub shuffle_account: Account<'info, ShuffleAccount>,
+    pub party: Signer<'info>,
+    /// Heads-up tables reveal once the betting is over, before the pot is paid.
+    #[account(seeds = [b"hand", game_account.key().as_ref()], bump)]
+    pub hand_account: Option<Box<Account<'info, HandAccount>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct ShuffleSeedData {
+    pub value: [u8; 32],
+}
+
+// Heads-Up Hand
+
+/// The hand being played at a `GameMode::HeadsUp` table, reused from hand to
+/// hand. Index 0 is the button, who posts the small blind; index 1 is the big
+/// blind. Seat balances are only touched when the hand finishes, until then
+/// the chips in play are tracked here.
+#[account]
+pub struct HandAccount {
+    pub game: Pubkey,                      // 32
+    pub hand: u64,                         // 8
+    pub players: [Pubkey; 2],              // 64
+    pub stacks: [u64; 2],                  // 16, chips behind
+    pub committed: [u64; 2],               // 16, chips put in this hand
+    pub street_bets: [u64; 2],             // 16, chips put in this street
+    pub acted: [bool; 2],                  // 2, since the last bet or raise
+    pub street: Street,                    // 1
+    pub betting_closed: bool,              // 1, waiting on the board or showdown
+    pub to_act: u8,                        // 1
+    pub last_raise: u64,                   // 8
+    pub big_blind: u64,                    // 8
+    pub action_deadline: i64,              // 8
+    pub card_commitments: [[u8; 32]; 9],   // 288
+    pub sealed_hole_cards: [[u8; 128]; 2], // 256
+    pub board: Vec<u8>,                    // 4 + 5
+    pub hole_cards: [[u8; 2]; 2],          // 4
+    pub hole_cards_revealed: [bool; 2],    // 2
+    pub outcome: Option<Outcome>,          // 3, waiting on the shuffle seeds;
+}
+
+/// Positions in `card_commitments`: each player's two hole cards, then the board.
+pub const HOLE_CARD_POSITIONS: [usize; 2] = [0, 2];
+pub const BOARD_POSITION: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Street {
+    #[default]
+    Preflop,
+    Flop,
+    Turn,
+    River,
+    Showdown,
+    Complete,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandAction {
+    Fold,
+    Check,
+    Call,
+    /// Bet or raise so this street's total bet is the given amount.
+    RaiseTo(u64),
+}
+
+/// A card opened against its commitment, `sha256([card] || salt)`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RevealedCard {
+    pub card: u8,
+    pub salt: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DealHandData {
+    pub card_commitments: [[u8; 32]; 9],
+    /// Each player's hole cards and salts, encrypted to them off-chain.
+    pub sealed_hole_cards: [[u8; 128]; 2],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PlayerActionData {
+    pub action: HandAction,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RevealBoardData {
+    pub cards: Vec<RevealedCard>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RevealHoleCardsData {
+    pub player: Pubkey,
+    pub cards: [RevealedCard; 2],
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct CreateGameParams<'info> {
-    #[account(init, payer = payer, space =  32 + 2 + 8 + 8 + 2 + 8 + 424 + 2 + 32)]
+pub struct DealHandAccounts<'info> {
+    #[account(
+        mut,
+        constraint = game_account.is_operator(&payer.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &payer.key(), Permission::Deal)
+    )]
     pub game_account: Account<'info, GameAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 8 + 64 + 16 + 16 + 16 + 2 + 1 + 1 + 1 + 8 + 8 + 8 + 288 + 256 + 9 + 4 + 2 + 3,
+        seeds = [b"hand", game_account.key().as_ref()],
+        bump
+    )]
+    pub hand_account: Box<Account<'info, HandAccount>>,
+    /// Every hand is dealt from a locked commit-reveal round.
+    #[account(mut, seeds = [b"shuffle", game_account.key().as_ref()], bump)]
+    pub shuffle_account: Account<'info, ShuffleAccount>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
     pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct GameAccount {
-    pub owner: Pubkey,                   // 32
-    pub max_players: u16,                // 2
-    pub min_deposit: u64,                // 8
-    pub max_deposit: u64,                // 8
-    pub players: Box<Vec<SeatedPlayer>>, // 4 + 42 * 10 enough for 10 players;
-    pub token_mint: Pubkey,              // 32
+// Player actions, hole card reveals and timeouts; `payer` is whoever signs;
+#[event_cpi]
+#[derive(Accounts)]
+pub struct HandActionAccounts<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(mut, seeds = [b"hand", game_account.key().as_ref()], bump)]
+    pub hand_account: Box<Account<'info, HandAccount>>,
+    pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
-// Join Game
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevealBoardAccounts<'info> {
+    #[account(
+        mut,
+        constraint = game_account.is_operator(&payer.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &payer.key(), Permission::Deal)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(mut, seeds = [b"hand", game_account.key().as_ref()], bump)]
+    pub hand_account: Box<Account<'info, HandAccount>>,
+    pub payer: Signer<'info>,
+    pub role: Option<Account<'info, RoleAccount>>,
+}
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct JoinGame<'info> {
+pub struct SettleHeadsUpHandAccounts<'info> {
     #[account(mut)]
     pub game_account: Account<'info, GameAccount>,
+    #[account(mut, seeds = [b"hand", game_account.key().as_ref()], bump)]
+    pub hand_account: Box<Account<'info, HandAccount>>,
+    #[account(seeds = [b"shuffle", game_account.key().as_ref()], bump)]
+    pub shuffle_account: Account<'info, ShuffleAccount>,
+    pub payer: Signer<'info>,
+}
+
+// Withdraw Rake
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawRakeAccounts<'info> {
+    #[account(
+        mut,
+        constraint = can_manage(&game_account.owner, &game_account.club, &club, &payer.key())
+            || game_account.rake_recipient == payer.key()
+            || has_role(&role, &game_account.key(), &game_account.club, &payer.key(), Permission::Pay)
+    )]
+    pub game_account: Account<'info, GameAccount>,
     #[account(
-        mut, 
-        constraint = game_token_account.owner == pda_account.key(), 
+        mut,
+        constraint = game_token_account.owner == pda_account.key(),
         constraint = game_token_account.mint == game_account.token_mint
     )]
-    pub game_token_account: Account<'info, TokenAccount>,
+    pub game_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
-        mut, 
-        constraint = player_token_account.owner == player.key(),
-        constraint = player_token_account.mint == game_account.token_mint
+        mut,
+        constraint = rake_recipient_token_account.owner == game_account.rake_recipient,
+        constraint = rake_recipient_token_account.mint == game_account.token_mint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub player: Signer<'info>,
+    pub rake_recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: receives rake on native SOL tables;
+    #[account(mut, address = game_account.rake_recipient)]
+    pub rake_recipient: UncheckedAccount<'info>,
     /// CHECK: pda account has no state;
-    #[account(seeds = [
+    #[account(mut, seeds = [
         game_account.key().as_ref()
     ], bump)]
     pub pda_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
+    #[account(constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
-pub struct JoinGameArgs {
-    pub amount: u64,
+// Set Transactor
+#[derive(Accounts)]
+pub struct SetGameTransactorAccounts<'info> {
+    #[account(
+        mut,
+        constraint = can_manage(&game_account.owner, &game_account.club, &club, &owner.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &owner.key(), Permission::Configure)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
-pub struct SeatedPlayer {
-    pub address: Pubkey, // 32
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct SetTransactorData {
+    pub transactor: Pubkey,
 }
 
-// Add Chips
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct AddChipsData {
-    pub amount: u64,
+// Disputes
+#[derive(Accounts)]
+pub struct DisputeSettingsAccounts<'info> {
+    #[account(
+        mut,
+        constraint = can_manage(&game_account.owner, &game_account.club, &club, &owner.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &owner.key(), Permission::Configure)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct DisputeSettingsData {
+    /// Seconds an ejected player has to dispute their payout, zero to pay out straight away.
+    pub challenge_window: i64,
+    pub arbiter: Pubkey,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
-pub struct AddChips<'info> {
+pub struct DisputeSettlementAccounts<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    /// CHECK: must have signed the dispute, checked in the handler;
+    pub player: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+    /// CHECK: the instructions sysvar, holding the Ed25519 signature check;
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct DisputeSettlementData {
+    pub claimed: u64,
+    /// Hash of the player's signed record of the hand, kept off-chain for the arbiter.
+    pub evidence: [u8; 32],
+}
+
+// Release or resolve a pending payout;
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PaySettlementAccounts<'info> {
     #[account(mut)]
     pub game_account: Account<'info, GameAccount>,
     #[account(
-        mut, 
+        mut,
         constraint = game_token_account.owner == pda_account.key(),
         constraint = game_token_account.mint == game_account.token_mint
     )]
-    pub game_token_account: Account<'info, TokenAccount>,
+    pub game_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
-        mut, 
+        mut,
         constraint = player_token_account.owner == player.key(),
         constraint = player_token_account.mint == game_account.token_mint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: only receives the payout held for it;
     #[account(mut)]
-    pub player: Signer<'info>,
+    pub player: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
     /// CHECK: pda account has no state;
-    #[account(seeds = [
+    #[account(mut, seeds = [
         game_account.key().as_ref()
     ], bump)]
     pub pda_account: UncheckedAccount<'info>,
+    #[account(constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-// Eject Player
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct ResolveDisputeData {
+    /// What the player is paid, at most what was held back.
+    pub amount: u64,
+}
+
+// Signed cash-outs
 #[derive(Accounts)]
-pub struct EjectPlayersAccounts<'info> {
+pub struct SignedCashOutSettingsAccounts<'info> {
     #[account(
-        mut, 
-        constraint = game_account.owner == payer.key()
+        mut,
+        constraint = can_manage(&game_account.owner, &game_account.club, &club, &owner.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &owner.key(), Permission::Configure)
     )]
     pub game_account: Account<'info, GameAccount>,
-    #[account(
-        mut, 
-        constraint = token_account.owner == pda_account.key(),
-        constraint = game_account.token_mint == token_account.mint
-    )]
-    pub token_account: Account<'info, TokenAccount>,
     #[account(mut)]
-    pub payer: Signer<'info>,
-    /// CHECK: pda account has no state;
-    #[account(seeds = [
-        game_account.key().as_ref()
-    ], bump)]
-    pub pda_account: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub owner: Signer<'info>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
-pub struct EjectPlayersParams {
-    pub amounts: Vec<u64>,
+pub struct SignedCashOutSettingsData {
+    /// Turns `eject_players` off, so payouts need the player's signature.
+    pub required: bool,
 }
 
-
-// Refund Player
+#[event_cpi]
 #[derive(Accounts)]
-pub struct RefundPlayerAccounts<'info> {
+pub struct SignedCashOutAccounts<'info> {
     #[account(mut)]
     pub game_account: Account<'info, GameAccount>,
     #[account(
-        mut, 
+        mut,
         constraint = game_token_account.owner == pda_account.key(),
         constraint = game_token_account.mint == game_account.token_mint
     )]
-    pub game_token_account: Account<'info, TokenAccount>,
+    pub game_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
-        mut, 
+        mut,
+        constraint = player_token_account.owner == player.key(),
         constraint = player_token_account.mint == game_account.token_mint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: must have signed the statement, checked in the handler;
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
     /// CHECK: pda account has no state;
-    #[account(seeds = [
+    #[account(mut, seeds = [
         game_account.key().as_ref()
     ], bump)]
     pub pda_account: UncheckedAccount<'info>,
+    #[account(mut, constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    /// CHECK: the instructions sysvar, holding the Ed25519 signature checks;
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-
+/// What `seat` at the game holds as of `nonce`, see `statement`.
 #[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
-pub struct RefundPlayerParams {
+pub struct BalanceStatement {
+    pub seat: u8,
+    pub nonce: u64,
+    pub balance: u64,
+}
+
+// Game Status
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GameStatusAccounts<'info> {
+    #[account(
+        mut,
+        constraint = can_manage(&game_account.owner, &game_account.club, &club, &owner.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &owner.key(), Permission::Configure)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
+}
+
+// Waitlist
+
+/// A queued player's escrowed buy-in. The tokens sit in a token account owned
+/// by this PDA until the player is seated or leaves the waitlist.
+#[account]
+pub struct WaitlistAccount {
+    pub game: Pubkey,   // 32
+    pub player: Pubkey, // 32
+    pub amount: u64,    // 8
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct JoinWaitlistArgs {
     pub amount: u64,
 }
 
+#[derive(Accounts)]
+pub struct JoinWaitlistAccounts<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        init,
+        payer = player,
+        space = 8 + 32 + 32 + 8,
+        seeds = [
+            b"waitlist",
+            game_account.key().as_ref(),
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub waitlist_account: Account<'info, WaitlistAccount>,
+    #[account(
+        mut,
+        constraint = escrow_token_account.owner == waitlist_account.key(),
+        constraint = escrow_token_account.mint == game_account.token_mint
+    )]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key(),
+        constraint = player_token_account.mint == game_account.token_mint
+    )]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+    pub club: Option<Account<'info, Club>>,
+    #[account(constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveWaitlistAccounts<'info> {
+    #[account(mut)]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"waitlist",
+            game_account.key().as_ref(),
+            player.key().as_ref()
+        ],
+        bump,
+        close = player
+    )]
+    pub waitlist_account: Account<'info, WaitlistAccount>,
+    #[account(
+        mut,
+        constraint = escrow_token_account.owner == waitlist_account.key(),
+        constraint = escrow_token_account.mint == game_account.token_mint
+    )]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key(),
+        constraint = player_token_account.mint == game_account.token_mint
+    )]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+    #[account(mut, constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
+#[derive(Accounts)]
+pub struct RemoveFromWaitlistAccounts<'info> {
+    #[account(
+        mut,
+        constraint = can_manage(&game_account.owner, &game_account.club, &club, &owner.key())
+            || has_role(&role, &game_account.key(), &game_account.club, &owner.key(), Permission::Configure)
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    #[account(
+        mut,
+        seeds = [
+            b"waitlist",
+            game_account.key().as_ref(),
+            player.key().as_ref()
+        ],
+        bump,
+        close = player
+    )]
+    pub waitlist_account: Account<'info, WaitlistAccount>,
+    #[account(
+        mut,
+        constraint = escrow_token_account.owner == waitlist_account.key(),
+        constraint = escrow_token_account.mint == game_account.token_mint
+    )]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key(),
+        constraint = player_token_account.mint == game_account.token_mint
+    )]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub player: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
+    #[account(mut, constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
 // Close game
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseGame<'info> {
-    #[account(mut, constraint = game_account.owner == payer.key(),  close = payer)]
+    #[account(mut, constraint = game_account.owner == owner.key(),  close = owner)]
     pub game_account: Account<'info, GameAccount>,
     #[account(
         mut, 
         constraint = game_token_account.owner == pda_account.key(),
         constraint = game_token_account.mint == game_account.token_mint
     )]
-    pub game_token_account: Account<'info, TokenAccount>,
+    pub game_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut, 
-        constraint = payer_token_account.owner == payer.key(),
-        constraint = payer_token_account.mint == game_account.token_mint
+        constraint = owner_token_account.owner == game_account.owner,
+        constraint = owner_token_account.mint == game_account.token_mint
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = rake_recipient_token_account.owner == game_account.rake_recipient,
+        constraint = rake_recipient_token_account.mint == game_account.token_mint
     )]
-    pub payer_token_account: Account<'info, TokenAccount>,
+    pub rake_recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: receives rake on native SOL tables;
+    #[account(mut, address = game_account.rake_recipient)]
+    pub rake_recipient: UncheckedAccount<'info>,
     /// CHECK: its a pda fam;
     #[account(
+        mut,
         seeds = [game_account.key().as_ref()],
         bump
     )]
     pub pda_account: UncheckedAccount<'info>,
     #[account(mut)]
+    pub owner: SystemAccount<'info>,
+    #[account(mut)]
     pub payer: Signer<'info>,
+    pub club: Option<Account<'info, Club>>,
+    #[account(mut, constraint = mint.key() == game_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 
@@ -215,15 +1509,37 @@ pub struct CreateTournamentData {
     pub guarantee: u64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(data: CreateTournamentData)]
 pub struct CreateTournamentParams<'info> {
-    #[account(init, payer = owner, space =  2 + 8 + 8 + 200 + 32 + 32 + 1 + 1 + 1 + 8 + 32 + 2 + 200)]
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 8 + 8,
+        seeds = [b"host", owner.key().as_ref()],
+        bump
+    )]
+    pub host_registry: Account<'info, HostRegistry>,
+    #[account(
+        init,
+        payer = owner,
+        space =  2 + 8 + 8 + 200 + 32 + 32 + 1 + 1 + 1 + 8 + 32 + 2 + 200 + 32,
+        seeds = [
+            b"tournament",
+            owner.key().as_ref(),
+            host_registry.tournaments_created.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
     pub tournament_account: Account<'info, TournamentAccount>,
     #[account(mut)]
     pub owner: Signer<'info>,
+    #[account(constraint = club.is_admin(&owner.key()) @ PokerError::NotClubAdmin)]
+    pub club: Option<Account<'info, Club>>,
     #[account(mut)]
     pub transactor: SystemAccount<'info>,
-    #[account(seeds = [
+    #[account(mut, seeds = [
         tournament_account.key().as_ref()
     ], bump)]
     pub pda_account: SystemAccount<'info>,
@@ -231,14 +1547,16 @@ pub struct CreateTournamentParams<'info> {
         mut, 
         constraint = owner_token_account.owner == owner.key()
     )]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut, 
         constraint = tournament_token_account.owner == pda_account.key()
     )]
-    pub tournament_token_account: Account<'info, TokenAccount>,
+    pub tournament_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(constraint = mint.key() == resolve_token_mint(&club, data.token_mint))]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[account]
@@ -271,6 +1589,8 @@ pub struct TournamentAccount {
     pub guarantee: u64,
     // 200
     pub nft_payouts: Vec<u16>,
+    // 32, default when not under a club
+    pub club: Pubkey,
 }
 
 #[derive(Accounts)]
@@ -292,27 +1612,31 @@ pub struct AddNFTTournamentPrizeParams<'info> {
         constraint = tournament_nft_token_account.owner == tournament_nft_payout_account.key(), 
         constraint = tournament_nft_token_account.mint == owner_nft_token_account.mint
     )]
-    pub tournament_nft_token_account: Account<'info, TokenAccount>,
+    pub tournament_nft_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut, 
         constraint = owner_nft_token_account.owner == owner.key(),
         constraint = owner_nft_token_account.amount == 1,
     )]
-    pub owner_nft_token_account: Account<'info, TokenAccount>,
-    #[account(mut, constraint = owner.key() == tournament_account.owner)]
+    pub owner_nft_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = can_manage(&tournament_account.owner, &tournament_account.club, &club, &owner.key())
+            || has_role(&role, &tournament_account.key(), &tournament_account.club, &owner.key(), Permission::Configure)
+    )]
     pub owner: Signer<'info>,
+    #[account(constraint = nft_mint.key() == owner_nft_token_account.mint)]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
 #[derive(Accounts)]
 #[instruction(place_paid: u16)]
 pub struct RemoveNftTournamentPrizeParams<'info> {
-    #[account(
-        mut, 
-        constraint = tournament_account.owner == owner.key(),
-        constraint = tournament_account.has_started == false,
-    )]
+    #[account(mut, constraint = tournament_account.has_started == false)]
     pub tournament_account: Account<'info, TournamentAccount>,
     #[account(
         mut,
@@ -327,18 +1651,27 @@ pub struct RemoveNftTournamentPrizeParams<'info> {
         mut, 
         constraint = tournament_nft_token_account.owner == tournament_nft_payout_account.key(), 
         constraint = tournament_nft_token_account.mint == owner_nft_token_account.mint,
-        constraint = owner_nft_token_account.amount == 1,
+        constraint = tournament_nft_token_account.amount == 1,
     )]
-    pub tournament_nft_token_account: Account<'info, TokenAccount>,
+    pub tournament_nft_token_account: InterfaceAccount<'info, TokenAccount>,
+    // pulled prizes go back to the tournament owner, whoever pulls them;
     #[account(
         mut, 
-        constraint = owner_nft_token_account.owner == owner.key()
+        constraint = owner_nft_token_account.owner == tournament_account.owner
+    )]
+    pub owner_nft_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = can_manage(&tournament_account.owner, &tournament_account.club, &club, &owner.key())
+            || has_role(&role, &tournament_account.key(), &tournament_account.club, &owner.key(), Permission::Configure)
     )]
-    pub owner_nft_token_account: Account<'info, TokenAccount>,
-    #[account(mut, constraint = owner.key() == tournament_account.owner)]
     pub owner: Signer<'info>,
+    #[account(constraint = nft_mint.key() == owner_nft_token_account.mint)]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
@@ -346,6 +1679,7 @@ pub struct NftTournamentPrizeData {
     pub place_paid: u16,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct JoinTournamentParams<'info> {
     #[account(mut)]
@@ -355,13 +1689,13 @@ pub struct JoinTournamentParams<'info> {
         constraint = tournament_token_account.owner == pda_account.key(), 
         constraint = tournament_token_account.mint == tournament_account.token_mint
     )]
-    pub tournament_token_account: Account<'info, TokenAccount>,
+    pub tournament_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut, 
         constraint = player_token_account.owner == player.key(),
         constraint = player_token_account.mint == tournament_account.token_mint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
     init, 
     payer = player, 
@@ -376,12 +1710,15 @@ pub struct JoinTournamentParams<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
     /// CHECK: pda account has no state;
-    #[account(seeds = [
+    #[account(mut, seeds = [
         tournament_account.key().as_ref()
     ], bump)]
     pub pda_account: UncheckedAccount<'info>,
+    pub club: Option<Account<'info, Club>>,
+    #[account(constraint = mint.key() == tournament_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[account]
@@ -389,16 +1726,23 @@ pub struct TournamentPlayerAccount {
     pub position_finished: u16, // 2
     pub has_busted: bool,       // 1
     pub rebuys: u16,            // 2
+    pub paid: u64,              // 8
 }
 
 
 #[derive(Accounts)]
 pub struct UpdateTournamentPayoutsParams<'info> {
-    #[account(mut, constraint = tournament_account.owner == owner.key())]
+    #[account(
+        mut,
+        constraint = can_manage(&tournament_account.owner, &tournament_account.club, &club, &owner.key())
+            || has_role(&role, &tournament_account.key(), &tournament_account.club, &owner.key(), Permission::Configure)
+    )]
     pub tournament_account: Account<'info, TournamentAccount>,
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
@@ -408,16 +1752,24 @@ pub struct UpdateTournamentPayoutData {
 
 
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct FlipTournamentRegistrationParams<'info> {
-    #[account(mut, constraint = tournament_account.owner == owner.key())]
+    #[account(
+        mut,
+        constraint = can_manage(&tournament_account.owner, &tournament_account.club, &club, &owner.key())
+            || has_role(&role, &tournament_account.key(), &tournament_account.club, &owner.key(), Permission::Configure)
+    )]
     pub tournament_account: Account<'info, TournamentAccount>,
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UnregisterTournamentParams<'info> {
     #[account(mut)]
@@ -437,25 +1789,28 @@ pub struct UnregisterTournamentParams<'info> {
         constraint = tournament_token_account.owner == pda_account.key(), 
         constraint = tournament_token_account.mint == tournament_account.token_mint
     )]
-    pub tournament_token_account: Account<'info, TokenAccount>,
+    pub tournament_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut, 
         constraint = player_token_account.owner == player.key(),
         constraint = player_token_account.mint == tournament_account.token_mint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: pda account has no state;
-    #[account(seeds = [
+    #[account(mut, seeds = [
         tournament_account.key().as_ref()
     ], bump)]
     pub pda_account: UncheckedAccount<'info>,
     #[account(mut)]
     pub player: Signer<'info>,
+    #[account(constraint = mint.key() == tournament_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RefundTournamentParams<'info> {
     #[account(mut)]
@@ -464,7 +1819,7 @@ pub struct RefundTournamentParams<'info> {
         mut,
         seeds = [
             tournament_account.key().as_ref(),
-            player_token_account.owner.as_ref()
+            player.key().as_ref()
         ], 
         bump,
         close = player
@@ -475,36 +1830,53 @@ pub struct RefundTournamentParams<'info> {
         constraint = tournament_token_account.owner == pda_account.key(), 
         constraint = tournament_token_account.mint == tournament_account.token_mint
     )]
-    pub tournament_token_account: Account<'info, TokenAccount>,
+    pub tournament_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut, 
+        constraint = player_token_account.owner == player.key(),
         constraint = player_token_account.mint == tournament_account.token_mint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: pda account has no state;
-    #[account(seeds = [
+    #[account(mut, seeds = [
         tournament_account.key().as_ref()
     ], bump)]
     pub pda_account: UncheckedAccount<'info>,
-    #[account(mut, constraint = owner.key() == tournament_account.owner)]
+    #[account(
+        mut,
+        constraint = can_manage(&tournament_account.owner, &tournament_account.club, &club, &owner.key())
+            || has_role(&role, &tournament_account.key(), &tournament_account.club, &owner.key(), Permission::Pay)
+    )]
     pub owner: Signer<'info>,
     #[account(mut)]
     pub player: SystemAccount<'info>,
+    #[account(constraint = mint.key() == tournament_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct StartTournamentParams<'info> {
-    #[account(mut, constraint = tournament_account.owner == owner.key())]
+    #[account(
+        mut,
+        constraint = can_manage(&tournament_account.owner, &tournament_account.club, &club, &owner.key())
+            || has_role(&role, &tournament_account.key(), &tournament_account.club, &owner.key(), Permission::Configure)
+    )]
     pub tournament_account: Account<'info, TournamentAccount>,
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct BustTournamentParams<'info> {
     #[account(mut)]
@@ -513,7 +1885,7 @@ pub struct BustTournamentParams<'info> {
         mut,
         seeds = [
             tournament_account.key().as_ref(),
-            player_token_account.owner.as_ref()
+            player.key().as_ref()
         ], 
         bump,
         close = player
@@ -524,51 +1896,73 @@ pub struct BustTournamentParams<'info> {
         constraint = tournament_token_account.owner == pda_account.key(), 
         constraint = tournament_token_account.mint == tournament_account.token_mint
     )]
-    pub tournament_token_account: Account<'info, TokenAccount>,
+    pub tournament_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut, 
+        constraint = player_token_account.owner == player.key(),
         constraint = player_token_account.mint == tournament_account.token_mint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: pda account has no state;
-    #[account(seeds = [
+    #[account(mut, seeds = [
         tournament_account.key().as_ref()
     ], bump)]
     pub pda_account: UncheckedAccount<'info>,
-    #[account(mut, constraint = owner.key() == tournament_account.owner)]
+    #[account(
+        mut,
+        constraint = can_manage(&tournament_account.owner, &tournament_account.club, &club, &owner.key())
+            || has_role(&role, &tournament_account.key(), &tournament_account.club, &owner.key(), Permission::Deal)
+    )]
     pub owner: Signer<'info>,
     #[account(mut)]
     pub player: SystemAccount<'info>,
+    #[account(constraint = mint.key() == tournament_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,   
+    pub token_program: Interface<'info, TokenInterface>,   
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
 
 
 
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseTournamentParams<'info> {
-    #[account(mut, constraint = tournament_account.owner == owner.key(), close = owner)]
+    #[account(
+        mut,
+        constraint = can_manage(&tournament_account.owner, &tournament_account.club, &club, &payer.key())
+            || has_role(&role, &tournament_account.key(), &tournament_account.club, &payer.key(), Permission::Configure),
+        close = owner
+    )]
     pub tournament_account: Account<'info, TournamentAccount>,
     #[account(
         mut, 
         constraint = tournament_token_account.owner == pda_account.key(), 
         constraint = tournament_token_account.mint == tournament_account.token_mint
     )]
-    pub tournament_token_account: Account<'info, TokenAccount>,
+    pub tournament_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
-        constraint = owner_token_account.owner == owner.key(),
+        constraint = owner_token_account.owner == tournament_account.owner,
         constraint = owner_token_account.mint == tournament_account.token_mint
     )]
-    pub owner_token_account: Account<'info, TokenAccount>, 
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>, 
     /// CHECK: pda account has no state;
-    #[account(seeds = [
+    #[account(mut, seeds = [
         tournament_account.key().as_ref()
     ], bump)]
     pub pda_account: UncheckedAccount<'info>,
+    // what's left in the tournament goes to its owner, whoever closes it;
+    #[account(mut, address = tournament_account.owner)]
+    pub owner: SystemAccount<'info>,
     #[account(mut)]
-    pub owner: Signer<'info>,
-    pub token_program: Program<'info, Token>,   
+    pub payer: Signer<'info>,
+    #[account(mut, constraint = mint.key() == tournament_account.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,   
     pub system_program: Program<'info, System>,
+    pub club: Option<Account<'info, Club>>,
+    pub role: Option<Account<'info, RoleAccount>>,
 }
\ No newline at end of file
@@ -0,0 +1,82 @@
+//! Player-signed balance statements, the cash-out side of a simple payment
+//! channel.
+//!
+//! A statement says what a seat holds as of a nonce. The player and the
+//! table's owner or transactor each sign `balance_message` off-chain, and
+//! whoever cashes it out puts one Ed25519 program instruction per signature
+//! ahead of `cash_out_signed` in the same transaction. The runtime verifies
+//! those signatures before the program runs, so all that's left here is to
+//! find them through the Instructions sysvar and match the keys and message.
+//!
+//! Disputes over a held payout are signed the same way, over
+//! `dispute_message`, so the player's claim stands on its own.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+
+/// Keeps a statement from passing for any other signed message.
+pub const STATEMENT_DOMAIN: &[u8; 20] = b"degods-poker:balance";
+
+/// `STATEMENT_DOMAIN || game || seat || nonce || balance`, integers little-endian.
+pub fn balance_message(game: &Pubkey, seat: u8, nonce: u64, balance: u64) -> [u8; 69] {
+    let mut message = [0u8; 69];
+    message[..20].copy_from_slice(STATEMENT_DOMAIN);
+    message[20..52].copy_from_slice(game.as_ref());
+    message[52] = seat;
+    message[53..61].copy_from_slice(&nonce.to_le_bytes());
+    message[61..].copy_from_slice(&balance.to_le_bytes());
+    message
+}
+
+/// Keeps a dispute from passing for a balance statement and vice versa.
+pub const DISPUTE_DOMAIN: &[u8; 20] = b"degods-poker:dispute";
+
+/// `DISPUTE_DOMAIN || game || nonce || claimed || evidence`, where `nonce` is
+/// the disputed payout's, integers little-endian.
+pub fn dispute_message(game: &Pubkey, nonce: u64, claimed: u64, evidence: &[u8; 32]) -> [u8; 100] {
+    let mut message = [0u8; 100];
+    message[..20].copy_from_slice(DISPUTE_DOMAIN);
+    message[20..52].copy_from_slice(game.as_ref());
+    message[52..60].copy_from_slice(&nonce.to_le_bytes());
+    message[60..68].copy_from_slice(&claimed.to_le_bytes());
+    message[68..].copy_from_slice(evidence);
+    message
+}
+
+/// Every key the transaction's Ed25519 instructions show signing `message`.
+pub fn signers(instructions: &AccountInfo, message: &[u8]) -> Vec<Pubkey> {
+    let mut keys = Vec::new();
+    let mut index = 0;
+    while let Ok(instruction) = load_instruction_at_checked(index, instructions) {
+        if instruction.program_id == ed25519_program::ID {
+            keys.extend(signers_in(&instruction.data, message));
+        }
+        index += 1;
+    }
+    keys
+}
+
+/// Size of one `Ed25519SignatureOffsets` entry after the two-byte header.
+const OFFSETS_SIZE: usize = 14;
+
+/// Signers in one Ed25519 instruction. Only keys and messages carried in the
+/// instruction itself count, as pointing at another instruction's data could
+/// pass off a signature over something else.
+fn signers_in<'a>(data: &'a [u8], message: &'a [u8]) -> impl Iterator<Item = Pubkey> + 'a {
+    let count = data.first().copied().unwrap_or(0) as usize;
+    (0..count).filter_map(move |i| {
+        let start = 2 + i * OFFSETS_SIZE;
+        let offsets = data.get(start..start + OFFSETS_SIZE)?;
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+        if read(2) != u16::MAX || read(6) != u16::MAX || read(12) != u16::MAX {
+            return None;
+        }
+        let key_at = read(4) as usize;
+        let (message_at, message_len) = (read(8) as usize, read(10) as usize);
+        if data.get(message_at..message_at + message_len)? != message {
+            return None;
+        }
+        Pubkey::try_from(data.get(key_at..key_at + 32)?).ok()
+    })
+}
@@ -0,0 +1,245 @@
+use crate::errors::PokerError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+
+// Token movements shared by cash games and tournaments. Everything goes through
+// transfer_checked so the same code works for SPL Token and Token-2022 mints.
+//
+// Games and tournaments whose token_mint is the wrapped SOL mint run in native
+// mode instead: no mint or token accounts are passed, the PDA vault holds the
+// lamports itself and payouts land straight in player wallets.
+
+pub fn is_native(token_mint: &Pubkey) -> bool {
+    *token_mint == native_mint::ID
+}
+
+/// Native tables are driven without a mint account, token tables always need one.
+pub fn check_mint(token_mint: &Pubkey, mint: Option<&InterfaceAccount<'_, Mint>>) -> Result<()> {
+    if mint.is_some() == is_native(token_mint) {
+        return Err(PokerError::MintMismatch.into());
+    }
+    Ok(())
+}
+
+/// The account that actually holds `wallet`'s funds: its token account on token
+/// tables, the wallet itself on native SOL tables.
+pub fn holder<'info>(
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    token_account: &Option<InterfaceAccount<'info, TokenAccount>>,
+    wallet: AccountInfo<'info>,
+) -> Result<AccountInfo<'info>> {
+    match (mint, token_account) {
+        (Some(_), Some(token_account)) => Ok(token_account.to_account_info()),
+        (None, None) => Ok(wallet),
+        _ => Err(PokerError::MintMismatch.into()),
+    }
+}
+
+/// Where payouts for `wallet` go, used to match accounts passed in
+/// `remaining_accounts` against seats.
+pub fn payout_address(
+    mint: Option<&InterfaceAccount<'_, Mint>>,
+    token_program: &Pubkey,
+    wallet: &Pubkey,
+) -> Pubkey {
+    match mint {
+        Some(mint) => {
+            get_associated_token_address_with_program_id(wallet, &mint.key(), token_program)
+        }
+        None => *wallet,
+    }
+}
+
+/// What a vault can pay out. Native vaults keep their rent-exempt reserve so
+/// they never drop into a state the runtime would reject.
+pub fn vault_balance(
+    mint: Option<&InterfaceAccount<'_, Mint>>,
+    vault: &AccountInfo,
+) -> Result<u64> {
+    match mint {
+        Some(_) => balance(mint, vault),
+        None => Ok(vault
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0))),
+    }
+}
+
+/// Tops a native vault up to its rent-exempt reserve so the first buy-in can
+/// land in it. Token vaults are plain token accounts and need nothing here.
+pub fn fund_vault<'info>(
+    system_program: &Program<'info, System>,
+    payer: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+) -> Result<()> {
+    let reserve = Rent::get()?.minimum_balance(0);
+    if vault.lamports() >= reserve {
+        return Ok(());
+    }
+    let amount = reserve - vault.lamports();
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer,
+                to: vault,
+            },
+        ),
+        amount,
+    )
+}
+
+/// Moves `amount` between two holders. Pass `&[]` as `signer_seeds` when the
+/// authority signed the transaction itself. On native tables `from` pays and
+/// signs, so `authority` is ignored.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    match mint {
+        Some(mint) => transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from,
+                    mint: mint.to_account_info(),
+                    to,
+                    authority,
+                },
+                signer_seeds,
+            ),
+            amount,
+            mint.decimals,
+        ),
+        None => system_program::transfer(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::Transfer { from, to },
+                signer_seeds,
+            ),
+            amount,
+        ),
+    }
+}
+
+/// Moves `amount` into `to` and returns what actually arrived, which is less
+/// than `amount` when the mint charges a Token-2022 transfer fee.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<u64> {
+    let before = balance(mint, &to)?;
+    transfer_tokens(
+        token_program,
+        system_program,
+        mint,
+        from,
+        to.clone(),
+        authority,
+        signer_seeds,
+        amount,
+    )?;
+    Ok(balance(mint, &to)? - before)
+}
+
+/// Native SOL escrows are program owned, so their lamports are moved directly
+/// rather than through the system program.
+pub fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let remaining = match from.lamports().checked_sub(amount) {
+        Some(remaining) => remaining,
+        None => return Err(PokerError::NotEnoughFunds.into()),
+    };
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+/// Closes an emptied vault. Token-2022 won't close an account holding withheld
+/// transfer fees, so those are harvested to the mint first. Native vaults are
+/// swept, rent reserve included.
+pub fn close_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    vault: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mint = match mint {
+        Some(mint) => mint,
+        None => {
+            let lamports = vault.lamports();
+            return transfer_tokens(
+                token_program,
+                system_program,
+                None,
+                vault,
+                destination,
+                authority,
+                signer_seeds,
+                lamports,
+            );
+        }
+    };
+    if token_program.key() == spl_token_2022::ID && has_transfer_fee(mint)? {
+        invoke(
+            &spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                &token_program.key(),
+                &mint.key(),
+                &[&vault.key()],
+            )?,
+            &[mint.to_account_info(), vault.clone()],
+        )?;
+    }
+    close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: vault,
+            destination,
+            authority,
+        },
+        signer_seeds,
+    ))
+}
+
+fn balance(mint: Option<&InterfaceAccount<'_, Mint>>, account: &AccountInfo) -> Result<u64> {
+    match mint {
+        Some(_) => {
+            let data = account.try_borrow_data()?;
+            Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+        }
+        None => Ok(account.lamports()),
+    }
+}
+
+fn has_transfer_fee(mint: &InterfaceAccount<'_, Mint>) -> Result<bool> {
+    let info = mint.to_account_info();
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(state.get_extension::<TransferFeeConfig>().is_ok())
+}
@@ -0,0 +1,189 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::PokerError;
+use crate::events::PlayerJoined;
+use crate::state::{GameAccount, GameStatus, SeatedPlayer, WaitlistAccount};
+use crate::vault::{close_vault, deposit_tokens, move_lamports, transfer_tokens};
+
+// Seats freed by a payout can go straight to the front of the waitlist, in
+// the same instruction, when the caller passes the waitlisted players'
+// accounts. Without them the waitlist is left alone, so nobody's cash-out
+// depends on someone else's queue.
+//
+// Payout instructions take their payees' accounts first in
+// `remaining_accounts`, then for each player seated from the waitlist: their
+// waitlist account, their escrow token account on token tables, and their
+// wallet, which gets the waitlist account's rent back.
+
+/// Splits `remaining_accounts` into payees and waitlisted players. The
+/// waitlist part starts at the first account this program owns, which is
+/// never a token account or a wallet.
+pub fn split_remaining<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+) -> (&'a [AccountInfo<'info>], &'a [AccountInfo<'info>]) {
+    let payees = accounts
+        .iter()
+        .take_while(|account| *account.owner != crate::ID)
+        .count();
+    accounts.split_at(payees)
+}
+
+/// Fills free seats from the front of the waitlist for as many players as
+/// `accounts` covers, moving each escrowed buy-in into the game vault. A
+/// closing table keeps its seats empty; its waitlist is refunded instead.
+pub fn seat_waitlisted<'info>(
+    game_account: &mut Account<'info, GameAccount>,
+    accounts: &[AccountInfo<'info>],
+    game_vault: &AccountInfo<'info>,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+) -> Result<Vec<PlayerJoined>> {
+    let mut joined = Vec::new();
+    if matches!(
+        game_account.status,
+        GameStatus::Closing | GameStatus::Closed
+    ) {
+        return Ok(joined);
+    }
+    let game_account_key = game_account.key();
+    let mut accounts = accounts.iter();
+    while let (Some(seat), Some(&player)) = (
+        game_account.lowest_free_seat(),
+        game_account.waitlist.first(),
+    ) {
+        let waitlist_info = match accounts.next() {
+            Some(account) => account,
+            None => break,
+        };
+        let escrow = match mint {
+            Some(_) => Some(InterfaceAccount::<TokenAccount>::try_from(next_account(
+                &mut accounts,
+            )?)?),
+            None => None,
+        };
+        let wallet = next_account(&mut accounts)?;
+        let (address, bump_seed) = Pubkey::find_program_address(
+            &[b"waitlist", game_account_key.as_ref(), player.as_ref()],
+            &crate::ID,
+        );
+        if waitlist_info.key() != address || wallet.key() != player {
+            return Err(PokerError::NotNextOnWaitlist.into());
+        }
+        if let Some(escrow) = &escrow {
+            if escrow.owner != address || escrow.mint != game_account.token_mint {
+                return Err(PokerError::NotNextOnWaitlist.into());
+            }
+        }
+        let waitlist_account = Account::<WaitlistAccount>::try_from(waitlist_info)?;
+        let seeds: &[&[u8]] = &[
+            b"waitlist",
+            game_account_key.as_ref(),
+            player.as_ref(),
+            &[bump_seed],
+        ];
+        let received = match &escrow {
+            Some(escrow) => {
+                let received = deposit_tokens(
+                    token_program,
+                    system_program,
+                    mint,
+                    escrow.to_account_info(),
+                    game_vault.clone(),
+                    waitlist_info.clone(),
+                    &[seeds],
+                    waitlist_account.amount,
+                )?;
+                close_vault(
+                    token_program,
+                    system_program,
+                    mint,
+                    escrow.to_account_info(),
+                    wallet.clone(),
+                    waitlist_info.clone(),
+                    &[seeds],
+                )?;
+                received
+            }
+            // native escrows are the waitlist account's own lamports above rent;
+            None => {
+                move_lamports(waitlist_info, game_vault, waitlist_account.amount)?;
+                waitlist_account.amount
+            }
+        };
+        waitlist_account.close(wallet.clone())?;
+        game_account.waitlist.remove(0);
+        let nonce = game_account.statement_nonce;
+        game_account.players.push(SeatedPlayer {
+            address: player,
+            deposited: received,
+            withdrawn: 0,
+            balance: received,
+            seat,
+            sitting_out: false,
+            sat_out_at: 0,
+            nonce,
+        });
+        joined.push(PlayerJoined {
+            game: game_account_key,
+            player,
+            seat,
+            amount: received,
+        });
+    }
+    Ok(joined)
+}
+
+/// The rest of a waitlisted player's accounts, once their waitlist account
+/// has been passed.
+fn next_account<'a, 'info>(
+    accounts: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+) -> Result<&'a AccountInfo<'info>> {
+    match accounts.next() {
+        Some(account) => Ok(account),
+        None => Err(PokerError::WaitlistAccountsMissing.into()),
+    }
+}
+
+/// Hands a waitlisted player's escrowed buy-in back. The caller closes the
+/// waitlist account to the player, which on native tables is the refund.
+#[allow(clippy::too_many_arguments)]
+pub fn refund_escrow<'info>(
+    game: &Pubkey,
+    player: &Pubkey,
+    waitlist_account: AccountInfo<'info>,
+    escrow: Option<&InterfaceAccount<'info, TokenAccount>>,
+    to: AccountInfo<'info>,
+    wallet: AccountInfo<'info>,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let escrow = match escrow {
+        Some(escrow) => escrow,
+        None => return Ok(()),
+    };
+    let (_pda, bump_seed) =
+        Pubkey::find_program_address(&[b"waitlist", game.as_ref(), player.as_ref()], &crate::ID);
+    let seeds: &[&[u8]] = &[b"waitlist", game.as_ref(), player.as_ref(), &[bump_seed]];
+    transfer_tokens(
+        token_program,
+        system_program,
+        mint,
+        escrow.to_account_info(),
+        to,
+        waitlist_account.clone(),
+        &[seeds],
+        escrow.amount,
+    )?;
+    close_vault(
+        token_program,
+        system_program,
+        mint,
+        escrow.to_account_info(),
+        wallet,
+        waitlist_account,
+        &[seeds],
+    )
+}
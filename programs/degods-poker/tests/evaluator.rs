@@ -0,0 +1,241 @@
+use std::collections::HashSet;
+
+use degods_poker::evaluator::{best_hand, best_omaha_hand, showdown, Category, HandRank, Rules};
+use degods_poker::shuffle;
+use degods_poker::GameVariant;
+
+/// Parses cards like "As Td 9c 2h"; short deck cards are shifted down so six
+/// is its lowest rank.
+fn cards(hand: &str, rules: Rules) -> Vec<u8> {
+    hand.split_whitespace()
+        .map(|card| {
+            let mut chars = card.chars();
+            let rank = "23456789TJQKA".find(chars.next().unwrap()).expect("rank") as u8;
+            let suit = "cdhs".find(chars.next().unwrap()).expect("suit") as u8;
+            match rules {
+                Rules::Standard => rank * 4 + suit,
+                Rules::ShortDeck => (rank - 4) * 4 + suit,
+            }
+        })
+        .collect()
+}
+
+fn rank(hand: &str, rules: Rules) -> HandRank {
+    best_hand(&cards(hand, rules), rules).expect(hand)
+}
+
+fn holdem(hand: &str) -> HandRank {
+    rank(hand, Rules::Standard)
+}
+
+/// Calls `f` with every `k`-card hand from a deck of `n` cards.
+fn for_each_hand(n: u8, k: usize, mut f: impl FnMut(&[u8])) {
+    let mut hand: Vec<u8> = (0..k as u8).collect();
+    loop {
+        f(&hand);
+        let mut i = k;
+        loop {
+            if i == 0 {
+                return;
+            }
+            i -= 1;
+            if hand[i] < n - (k - i) as u8 {
+                break;
+            }
+        }
+        hand[i] += 1;
+        for j in i + 1..k {
+            hand[j] = hand[j - 1] + 1;
+        }
+    }
+}
+
+fn category_counts(rules: Rules) -> ([u32; 9], usize) {
+    let mut counts = [0u32; 9];
+    let mut classes = HashSet::new();
+    for_each_hand(rules.deck_size(), 5, |hand| {
+        let rank = best_hand(hand, rules).unwrap();
+        counts[rank.category() as usize] += 1;
+        classes.insert(rank.strength());
+    });
+    (counts, classes.len())
+}
+
+#[test]
+fn every_five_card_hand() {
+    let (counts, classes) = category_counts(Rules::Standard);
+    assert_eq!(
+        counts,
+        [1302540, 1098240, 123552, 54912, 10200, 5108, 3744, 624, 40]
+    );
+    assert_eq!(classes, 7462);
+}
+
+#[test]
+fn every_short_deck_hand() {
+    let (counts, _) = category_counts(Rules::ShortDeck);
+    assert_eq!(
+        counts,
+        [122400, 193536, 36288, 16128, 6120, 480, 1728, 288, 24]
+    );
+}
+
+#[test]
+fn categories_in_order() {
+    let ladder = [
+        "Ah Kd 9c 7s 3h",
+        "2c 2d 7h 8s 9c",
+        "2c 2d 3h 3s 4c",
+        "2c 2d 2h 3s 4c",
+        "Ah 2d 3c 4s 5h",
+        "2c 3d 4h 5s 6c",
+        "Tc Jd Qh Ks Ac",
+        "2h 4h 6h 8h Th",
+        "2c 2d 2h 3s 3c",
+        "2c 2d 2h 2s 3c",
+        "Ah 2h 3h 4h 5h",
+        "Th Jh Qh Kh Ah",
+    ];
+    for pair in ladder.windows(2) {
+        assert!(
+            holdem(pair[0]) < holdem(pair[1]),
+            "{} < {}",
+            pair[0],
+            pair[1]
+        );
+    }
+    assert_eq!(holdem("Th Jh Qh Kh Ah").category(), Category::StraightFlush);
+    assert_eq!(holdem("Ah 2d 3c 4s 5h").category(), Category::Straight);
+}
+
+#[test]
+fn kickers() {
+    let wins = [
+        ("Ac Ad Kh 4s 3c", "Ac Ad Qh Js Tc"),
+        ("Kc Kd 3h 3s 2c", "Qc Qd Jh Js Ac"),
+        ("Kc Kd 3h 3s 5c", "Kc Kd 3h 3s 4c"),
+        ("9c 9d 9h 2s 2c", "8c 8d 8h As Ac"),
+        ("Ah 9h 8h 7h 5h", "Kh Qh Jh Th 8h"),
+        ("2c 3d 4h 5s 6c", "Ah 2d 3c 4s 5h"),
+        ("Ac Kd 9h 7s 4c", "Ac Kd 9h 7s 3c"),
+    ];
+    for (better, worse) in wins {
+        assert!(holdem(better) > holdem(worse), "{} > {}", better, worse);
+    }
+    // suits never break ties;
+    assert_eq!(holdem("Ac Kd 9h 7s 4c"), holdem("As Kh 9d 7c 4h"));
+}
+
+#[test]
+fn best_five_of_seven() {
+    let vectors = [
+        // a third pair only counts as a kicker;
+        ("Ac Ad Kh Ks Qc Qd 2h", "Ac Ad Kh Ks Qc"),
+        // two sets make a full house;
+        ("9c 9d 9h 4s 4c 4d 2h", "9c 9d 9h 4s 4c"),
+        // six to a straight plays the top five;
+        ("4c 5d 6h 7s 8c 9d Kh", "5d 6h 7s 8c 9d"),
+        ("Ah 2c 3d 4s 5h 6c Kd", "2c 3d 4s 5h 6c"),
+        // six to a flush;
+        ("2h 5h 7h 9h Jh Kh Ac", "5h 7h 9h Jh Kh"),
+        ("2h 3h 4h 5h 6h 7h 8c", "3h 4h 5h 6h 7h"),
+        // quads with the best kicker on board;
+        ("7c 7d 7h 7s Kc Kd Ah", "7c 7d 7h 7s Ah"),
+        ("Ac Kd Qh Js 9c 8d 3h", "Ac Kd Qh Js 9c"),
+    ];
+    for (seven, five) in vectors {
+        assert_eq!(holdem(seven), holdem(five), "{}", seven);
+    }
+}
+
+/// Six and seven card hands agree with trying every five cards of them.
+#[test]
+fn matches_brute_force() {
+    for rules in [Rules::Standard, Rules::ShortDeck] {
+        let size = rules.deck_size() as usize;
+        for round in 0u32..4000 {
+            let seed = shuffle::deck_seed(&[[round as u8; 32], [(round >> 8) as u8; 32]]);
+            let deck = shuffle::permutation(&seed, size);
+            let hand = &deck[..6 + round as usize % 2];
+            let mut best = None;
+            for_each_hand(hand.len() as u8, 5, |picks| {
+                let five: Vec<u8> = picks.iter().map(|i| hand[*i as usize]).collect();
+                best = best.max(best_hand(&five, rules));
+            });
+            assert_eq!(best_hand(hand, rules), best, "{:?}", hand);
+        }
+    }
+}
+
+#[test]
+fn short_deck() {
+    let short = |hand| rank(hand, Rules::ShortDeck);
+    assert!(short("6h 8h 9h Jh Kh") > short("As Ad Ah Ks Kd"));
+    assert_eq!(short("Ac 6d 7h 8s 9c").category(), Category::Straight);
+    assert!(short("Ac 6d 7h 8s 9c") < short("6d 7h 8s 9c Td"));
+    assert_eq!(short("Ac 6c 7c 8c 9c").category(), Category::StraightFlush);
+    // a short deck stops at 36 cards;
+    assert_eq!(best_hand(&[0, 4, 8, 12, 36], Rules::ShortDeck), None);
+    assert_eq!(
+        showdown(
+            GameVariant::ShortDeck,
+            &cards("Ah Kh", Rules::ShortDeck),
+            &cards("Qh Jh Th 6c 6d", Rules::ShortDeck),
+        )
+        .unwrap()
+        .category(),
+        Category::StraightFlush
+    );
+}
+
+#[test]
+fn omaha_uses_two_hole_cards() {
+    let omaha = |hole: &str, board: &str| {
+        best_omaha_hand(
+            &cards(hole, Rules::Standard),
+            &cards(board, Rules::Standard),
+        )
+        .unwrap()
+    };
+    // four hearts on board and one in hand is no flush;
+    assert_eq!(
+        omaha("Ah Kc Qd Js", "2h 5h 8h 9h Tc").category(),
+        Category::Straight
+    );
+    assert_eq!(
+        omaha("Ah Kh Qd Js", "2h 5h 8h 9h Tc").category(),
+        Category::Flush
+    );
+    // a board full house only plays with two hole cards;
+    assert_eq!(
+        omaha("Ac Kd 7h 2s", "9c 9d 9h 4s 4c").category(),
+        Category::ThreeOfAKind
+    );
+    // nor do four aces in hand make quads;
+    assert_eq!(
+        omaha("Ac Ad Ah As", "2c 7d 9h Js Kc").category(),
+        Category::Pair
+    );
+    assert_eq!(
+        showdown(
+            GameVariant::Plo5,
+            &cards("Ac Ad Kh Ks 2c", Rules::Standard),
+            &cards("Ah 7d 7h Js Kc", Rules::Standard),
+        )
+        .unwrap()
+        .category(),
+        Category::FullHouse
+    );
+}
+
+#[test]
+fn rejects_impossible_hands() {
+    assert_eq!(best_hand(&[0, 1, 2, 3], Rules::Standard), None);
+    assert_eq!(best_hand(&[0, 1, 2, 3, 4, 5, 6, 7], Rules::Standard), None);
+    assert_eq!(best_hand(&[0, 1, 2, 3, 3], Rules::Standard), None);
+    assert_eq!(best_hand(&[0, 1, 2, 3, 52], Rules::Standard), None);
+    assert_eq!(best_omaha_hand(&[0, 1, 2], &[4, 5, 6, 7, 8]), None);
+    assert_eq!(best_omaha_hand(&[0, 1, 2, 3], &[3, 5, 6, 7, 8]), None);
+    assert_eq!(showdown(GameVariant::Plo, &[0, 1], &[4, 5, 6, 7, 8]), None);
+    assert_eq!(showdown(GameVariant::Nlhe, &[0, 1], &[4, 5]), None);
+}
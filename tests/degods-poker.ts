@@ -1,14 +1,27 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { DegodsPokerProgram } from "../target/types/degods_poker_program";
-import { PublicKey, Signer } from "@solana/web3.js";
+import {
+  Ed25519Program,
+  PublicKey,
+  Signer,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
 import {
   createAssociatedTokenAccount,
   createMint,
   mintTo,
   getOrCreateAssociatedTokenAccount,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  ExtensionType,
+  getMintLen,
+  NATIVE_MINT,
+  TOKEN_2022_PROGRAM_ID,
 } from "@solana/spl-token";
 import { describe, it } from "mocha";
+import { createHash } from "crypto";
 import chai from "chai";
 import chaiAsPromised from "chai-as-promised";
 chai.use(chaiAsPromised);
@@ -25,15 +38,196 @@ const program = anchor.workspace
   .DegodsPokerProgram as Program<DegodsPokerProgram>;
 const connection = anchor.getProvider().connection;
 
+// signs the self-CPI that carries emitted events
+const eventAuthority = PublicKey.findProgramAddressSync(
+  [Buffer.from("__event_authority")],
+  MY_PROGRAM_ID
+)[0];
+
+const findShuffle = (gameAccount: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("shuffle"), gameAccount.toBuffer()],
+    MY_PROGRAM_ID
+  )[0];
+
+const findHand = (gameAccount: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("hand"), gameAccount.toBuffer()],
+    MY_PROGRAM_ID
+  )[0];
+
+// opens a shuffle round and commits a seed for each player, so a hand can start
+const commitRound = async (
+  gameAccount: PublicKey,
+  dealer: Signer,
+  players: Signer[]
+) => {
+  const shuffleAccount = findShuffle(gameAccount);
+  const commitment = () => [
+    ...createHash("sha256")
+      .update(anchor.web3.Keypair.generate().publicKey.toBuffer())
+      .digest(),
+  ];
+  await program.methods
+    .openShuffle({ value: commitment() })
+    .accounts({
+      eventAuthority,
+      program: MY_PROGRAM_ID,
+      gameAccount,
+      shuffleAccount,
+      payer: dealer.publicKey,
+      role: null,
+    })
+    .signers([dealer])
+    .rpc();
+  for (const player of players) {
+    await program.methods
+      .commitShuffleSeed({ value: commitment() })
+      .accounts({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        gameAccount,
+        shuffleAccount,
+        party: player.publicKey,
+        handAccount: null,
+      })
+      .signers([player])
+      .rpc();
+  }
+  return shuffleAccount;
+};
+
+// commits a round and starts a hand from it, as settle_hand requires
+const beginHand = async (
+  gameAccount: PublicKey,
+  dealer: Signer,
+  players: Signer[]
+) => {
+  const shuffleAccount = await commitRound(gameAccount, dealer, players);
+  await program.methods
+    .startHand()
+    .accounts({
+      shuffleAccount,
+      role: null,
+      gameAccount,
+      payer: dealer.publicKey,
+    })
+    .signers([dealer])
+    .rpc();
+};
+
+// shuffle::permutation, so tests deal the deck the program checks hands against
+const permutation = (deckSeed: Buffer, deckSize: number) => {
+  const deck = [...Array(deckSize).keys()];
+  const max = new anchor.BN(1).shln(64).subn(1);
+  let counter = 0;
+  let block = Buffer.alloc(32);
+  let offset = 32;
+  const next = () => {
+    if (offset === 32) {
+      const index = new anchor.BN(counter++).toArrayLike(Buffer, "le", 8);
+      block = createHash("sha256").update(deckSeed).update(index).digest();
+      offset = 0;
+    }
+    offset += 8;
+    return new anchor.BN(block.subarray(offset - 8, offset), "le");
+  };
+  for (let i = deckSize - 1; i > 0; i--) {
+    const bound = new anchor.BN(i + 1);
+    // draws past the last whole multiple of the bound are thrown away
+    const zone = max.sub(max.mod(bound));
+    let draw = next();
+    while (draw.gte(zone)) {
+      draw = next();
+    }
+    const j = draw.mod(bound).toNumber();
+    [deck[i], deck[j]] = [deck[j], deck[i]];
+  }
+  return deck;
+};
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+const findHostRegistry = (owner: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("host"), owner.toBuffer()],
+    MY_PROGRAM_ID
+  )[0];
+
+const findClub = (owner: PublicKey, name: string) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("club"), owner.toBuffer(), Buffer.from(name)],
+    MY_PROGRAM_ID
+  )[0];
+
+const findRole = (scope: PublicKey, grantee: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("role"), scope.toBuffer(), grantee.toBuffer()],
+    MY_PROGRAM_ID
+  )[0];
+
+const findGameAccount = (owner: PublicKey, index: number) =>
+  PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("game"),
+      owner.toBuffer(),
+      new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+    ],
+    MY_PROGRAM_ID
+  )[0];
+
+// Token-2022 mint that withholds `feeBps` of every transfer
+const createTransferFeeMint = async (payer: Signer, feeBps: number) => {
+  const mint = anchor.web3.Keypair.generate();
+  const space = getMintLen([ExtensionType.TransferFeeConfig]);
+  const lamports = await connection.getMinimumBalanceForRentExemption(space);
+  const tx = new Transaction().add(
+    anchor.web3.SystemProgram.createAccount({
+      fromPubkey: payer.publicKey,
+      newAccountPubkey: mint.publicKey,
+      space,
+      lamports,
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    createInitializeTransferFeeConfigInstruction(
+      mint.publicKey,
+      payer.publicKey,
+      payer.publicKey,
+      feeBps,
+      BigInt(Number.MAX_SAFE_INTEGER),
+      TOKEN_2022_PROGRAM_ID
+    ),
+    createInitializeMintInstruction(
+      mint.publicKey,
+      9,
+      payer.publicKey,
+      null,
+      TOKEN_2022_PROGRAM_ID
+    )
+  );
+  await anchor.web3.sendAndConfirmTransaction(connection, tx, [payer, mint]);
+  return mint.publicKey;
+};
+
 const createGame = async (
   data: Partial<{
     minDeposit: anchor.BN;
     maxDeposit: anchor.BN;
     maxPlayers: number;
-  }> = {}
+    rakeBps: number;
+    rakeCap: anchor.BN;
+    inactivityTimeout: anchor.BN;
+    bigBlind: anchor.BN;
+    minBuyInBb: number;
+    maxBuyInBb: number;
+    maxSitOutSeconds: anchor.BN;
+    mode: object;
+  }> = {},
+  transferFeeBps?: number
 ) => {
   const payer = anchor.web3.Keypair.generate();
-  const gameAccount = anchor.web3.Keypair.generate();
+  const transactor = anchor.web3.Keypair.generate();
+  const gameAccount = findGameAccount(payer.publicKey, 0);
   // Specify the rent-exempt reserve to fund the account creation
   const airdropTx = await connection.requestAirdrop(
     payer.publicKey,
@@ -41,15 +235,13 @@ const createGame = async (
   );
   await connection.confirmTransaction(airdropTx);
   const decimals = 9;
-  const mint = await createMint(
-    connection,
-    payer,
-    payer.publicKey,
-    null,
-    decimals
-  );
+  const mint =
+    transferFeeBps === undefined
+      ? await createMint(connection, payer, payer.publicKey, null, decimals)
+      : await createTransferFeeMint(payer, transferFeeBps);
+  const { owner: tokenProgram } = await connection.getAccountInfo(mint);
   const [pda, bump] = PublicKey.findProgramAddressSync(
-    [gameAccount.publicKey.toBuffer()],
+    [gameAccount.toBuffer()],
     MY_PROGRAM_ID
   );
   const tokenAccount = await getOrCreateAssociatedTokenAccount(
@@ -57,7 +249,10 @@ const createGame = async (
     payer,
     mint,
     pda,
-    true
+    true,
+    undefined,
+    undefined,
+    tokenProgram
   );
   // Add your test here.
   await program.methods
@@ -66,16 +261,35 @@ const createGame = async (
       maxPlayers: 8,
       minDeposit: new anchor.BN(100 * Math.pow(10, decimals)),
       tokenMint: mint,
+      rakeBps: 0,
+      rakeCap: new anchor.BN(0),
+      rakeRecipient: payer.publicKey,
+      inactivityTimeout: new anchor.BN(3600),
+      smallBlind: new anchor.BN(1 * Math.pow(10, decimals)),
+      bigBlind: new anchor.BN(2 * Math.pow(10, decimals)),
+      ante: new anchor.BN(0),
+      variant: { nlhe: {} },
+      tableName: "1/2 NLHE",
+      minBuyInBb: 0,
+      maxBuyInBb: 0,
+      maxSitOutSeconds: new anchor.BN(0),
+      mode: { dealer: {} },
       ...data,
     })
     .accounts({
-      gameAccount: gameAccount.publicKey,
+      eventAuthority,
+      program: MY_PROGRAM_ID,
+      club: null,
+      hostRegistry: findHostRegistry(payer.publicKey),
+      gameAccount,
+      pdaAccount: pda,
       payer: payer.publicKey,
+      transactor: transactor.publicKey,
     })
-    .signers([payer, gameAccount])
+    .signers([payer])
     .rpc();
 
-  return { payer, gameAccount, mint, tokenAccount };
+  return { payer, transactor, gameAccount, mint, tokenAccount };
 };
 
 interface JoinGameArgs {
@@ -84,22 +298,37 @@ interface JoinGameArgs {
   amount: number;
   gameAccount: PublicKey;
   tokenAccount: PublicKey;
+  seat?: number;
+  club?: PublicKey;
 }
 
 const joinGame = async (req: JoinGameArgs) => {
   const { mint, payer, amount, gameAccount, tokenAccount } = req;
+  // default to the lowest open seat
+  const gameState = await program.account.gameAccount.fetch(gameAccount);
+  const taken = gameState.players.map((p) => p.seat);
+  const seat =
+    req.seat ??
+    [...Array(gameState.maxPlayers).keys()].find(
+      (s) => taken.indexOf(s) === -1
+    ) ??
+    0;
   const player = anchor.web3.Keypair.generate();
   const airdropTx = await connection.requestAirdrop(
     player.publicKey,
     2000000000
   );
   await connection.confirmTransaction(airdropTx);
+  const { owner: tokenProgram } = await connection.getAccountInfo(mint);
   const playerTokenAccount = await getOrCreateAssociatedTokenAccount(
     connection,
     player,
     mint,
     player.publicKey,
-    true
+    true,
+    undefined,
+    undefined,
+    tokenProgram
   );
   await mintTo(
     connection,
@@ -107,7 +336,10 @@ const joinGame = async (req: JoinGameArgs) => {
     mint,
     playerTokenAccount.address,
     payer,
-    amount
+    amount,
+    [],
+    undefined,
+    tokenProgram
   );
   const [pda, bump] = PublicKey.findProgramAddressSync(
     [gameAccount.toBuffer()],
@@ -116,13 +348,19 @@ const joinGame = async (req: JoinGameArgs) => {
   const tx = await program.methods
     .joinGame({
       amount: new anchor.BN(amount),
+      seat,
     })
     .accounts({
-      gameAccount: gameAccount,
+      eventAuthority,
+      program: MY_PROGRAM_ID,
+      club: req.club ?? null,
+      mint,
+      gameAccount,
       gameTokenAccount: tokenAccount,
       playerTokenAccount: playerTokenAccount.address,
       player: player.publicKey,
       pdaAccount: pda,
+      tokenProgram,
     })
     .signers([player])
     .rpc();
@@ -133,26 +371,51 @@ const joinGame = async (req: JoinGameArgs) => {
 describe("degods-poker-program", () => {
   it("Creates Game", async () => {
     const { gameAccount, payer } = await createGame();
-    const { data } = await connection.getAccountInfo(gameAccount.publicKey);
+    const { data } = await connection.getAccountInfo(gameAccount);
     const gameState = program.coder.accounts.decode("GameAccount", data);
     expect(gameState.owner).to.eql(payer.publicKey);
     expect(gameState.players.length).to.eq(0);
   });
+  it("Stores stakes and derives buy-in limits from the big blind", async () => {
+    const { gameAccount } = await createGame({
+      bigBlind: new anchor.BN(2 * Math.pow(10, 9)),
+      minBuyInBb: 40,
+      maxBuyInBb: 100,
+    });
+    const { data } = await connection.getAccountInfo(gameAccount);
+    const gameState = program.coder.accounts.decode("GameAccount", data);
+    expect(gameState.tableName).to.eq("1/2 NLHE");
+    expect(gameState.variant).to.eql({ nlhe: {} });
+    expect(gameState.minDeposit.toString()).to.eq("80000000000");
+    expect(gameState.maxDeposit.toString()).to.eq("200000000000");
+  });
+  it("Finds a host's games from the host registry", async () => {
+    const { gameAccount, payer } = await createGame();
+    const registry = await program.account.hostRegistry.fetch(
+      findHostRegistry(payer.publicKey)
+    );
+    expect(registry.owner).to.eql(payer.publicKey);
+    expect(registry.gamesCreated.toNumber()).to.eq(1);
+    expect(findGameAccount(payer.publicKey, 0)).to.eql(gameAccount);
+  });
   describe("Join Game", () => {
     it("happy path", async () => {
       const amount = 200 * Math.pow(10, 9);
       const { gameAccount, tokenAccount, mint, payer } = await createGame();
       const { player } = await joinGame({
         amount,
-        gameAccount: gameAccount.publicKey,
+        gameAccount,
         mint,
         payer,
         tokenAccount: tokenAccount.address,
       });
-      const { data } = await connection.getAccountInfo(gameAccount.publicKey);
+      const { data } = await connection.getAccountInfo(gameAccount);
       const gameState = program.coder.accounts.decode("GameAccount", data);
       expect(gameState.players.length).to.eq(1);
       expect(gameState.players[0].address).to.eql(player.publicKey);
+      expect(gameState.players[0].deposited.toString()).to.eq(`${amount}`);
+      expect(gameState.players[0].withdrawn.toString()).to.eq("0");
+      expect(gameState.players[0].balance.toString()).to.eq(`${amount}`);
     });
 
     it("fails when deposits are wrong amounts", async () => {
@@ -161,7 +424,7 @@ describe("degods-poker-program", () => {
       await expect(
         joinGame({
           amount: amount + 1,
-          gameAccount: gameAccount.publicKey,
+          gameAccount,
           mint,
           payer,
           tokenAccount: tokenAccount.address,
@@ -170,7 +433,7 @@ describe("degods-poker-program", () => {
       await expect(
         joinGame({
           amount: 100 * Math.pow(10, 9) - 1,
-          gameAccount: gameAccount.publicKey,
+          gameAccount,
           mint,
           payer,
           tokenAccount: tokenAccount.address,
@@ -178,34 +441,463 @@ describe("degods-poker-program", () => {
       ).to.eventually.rejected;
     });
 
+    it("rejects tables with more seats than the game account holds", async () => {
+      await expect(createGame({ maxPlayers: 11 })).to.eventually.rejected;
+    });
+
     it("fails if table is full", async () => {
       const { gameAccount, tokenAccount, mint, payer } = await createGame({
         maxPlayers: 2,
       });
       await joinGame({
         amount: 200 * Math.pow(10, 9),
-        gameAccount: gameAccount.publicKey,
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
         mint,
         payer,
         tokenAccount: tokenAccount.address,
       });
+      await expect(
+        joinGame({
+          amount: 200 * Math.pow(10, 9),
+          gameAccount,
+          mint,
+          payer,
+          tokenAccount: tokenAccount.address,
+        })
+      ).to.eventually.rejected;
+    });
+  });
+
+  describe("Game Status", () => {
+    it("rejects joins while paused and accepts them after resuming", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      await program.methods
+        .pauseGame()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          owner: payer.publicKey,
+          club: null,
+          role: null,
+        })
+        .signers([payer])
+        .rpc();
+      await expect(
+        joinGame({
+          amount: 200 * Math.pow(10, 9),
+          gameAccount,
+          mint,
+          payer,
+          tokenAccount: tokenAccount.address,
+        })
+      ).to.eventually.rejected;
+      await program.methods
+        .resumeGame()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          owner: payer.publicKey,
+          club: null,
+          role: null,
+        })
+        .signers([payer])
+        .rpc();
       await joinGame({
         amount: 200 * Math.pow(10, 9),
-        gameAccount: gameAccount.publicKey,
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+    });
+
+    it("closes a closing table once the last player leaves", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      await program.methods
+        .beginClosingGame()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          owner: payer.publicKey,
+          club: null,
+          role: null,
+        })
+        .signers([payer])
+        .rpc();
+      const [pda, bump] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await program.methods
+        .leaveTable()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint,
+          gameAccount,
+          gameTokenAccount: tokenAccount.address,
+          playerTokenAccount: playerTokenAccount.address,
+          player: player.publicKey,
+          pdaAccount: pda,
+          handAccount: null,
+        })
+        .signers([player])
+        .rpc();
+      const { data } = await connection.getAccountInfo(gameAccount);
+      const gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.status).to.eql({ closed: {} });
+    });
+  });
+
+  describe("Seats", () => {
+    it("rejects a seat that is already taken", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
         mint,
         payer,
         tokenAccount: tokenAccount.address,
+        seat: 3,
       });
+      const { data } = await connection.getAccountInfo(gameAccount);
+      const gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.players[0].seat).to.eq(3);
       await expect(
         joinGame({
           amount: 200 * Math.pow(10, 9),
-          gameAccount: gameAccount.publicKey,
+          gameAccount,
           mint,
           payer,
           tokenAccount: tokenAccount.address,
+          seat: 3,
         })
       ).to.eventually.rejected;
     });
+
+    it("auto-ejects players sat out past the limit", async () => {
+      const { gameAccount, tokenAccount, mint, payer, transactor } =
+        await createGame({ maxSitOutSeconds: new anchor.BN(1) });
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const { player: active, playerTokenAccount: activeTokenAccount } =
+        await joinGame({
+          amount: 200 * Math.pow(10, 9),
+          gameAccount,
+          mint,
+          payer,
+          tokenAccount: tokenAccount.address,
+        });
+      await program.methods
+        .setSittingOut({ sittingOut: true })
+        .accounts({ gameAccount, player: player.publicKey })
+        .signers([player])
+        .rpc();
+      await sleep(3000);
+      const [pda, bump] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await program.methods
+        .ejectSatOutPlayers()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          mint,
+          gameAccount,
+          tokenAccount: tokenAccount.address,
+          payer: transactor.publicKey,
+          pdaAccount: pda,
+        })
+        .remainingAccounts([
+          {
+            isSigner: false,
+            isWritable: true,
+            pubkey: playerTokenAccount.address,
+          },
+          // still playing, so skipped rather than failing the batch
+          {
+            isSigner: false,
+            isWritable: true,
+            pubkey: activeTokenAccount.address,
+          },
+        ])
+        .signers([transactor])
+        .rpc();
+      const { data } = await connection.getAccountInfo(gameAccount);
+      const gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.players.length).to.eq(1);
+      expect(gameState.players[0].address).to.eql(active.publicKey);
+      const ret = await connection.getTokenAccountBalance(
+        playerTokenAccount.address
+      );
+      expect(ret.value.amount).to.eq("200000000000");
+    });
+
+    it("holds auto-ejected payouts for the challenge window", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame({
+        maxSitOutSeconds: new anchor.BN(1),
+      });
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      await program.methods
+        .setDisputeSettings({
+          challengeWindow: new anchor.BN(60),
+          arbiter: payer.publicKey,
+        })
+        .accounts({
+          club: null,
+          role: null,
+          gameAccount,
+          owner: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      await program.methods
+        .setSittingOut({ sittingOut: true })
+        .accounts({ gameAccount, player: player.publicKey })
+        .signers([player])
+        .rpc();
+      await sleep(3000);
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await program.methods
+        .ejectSatOutPlayers()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          mint,
+          gameAccount,
+          tokenAccount: tokenAccount.address,
+          payer: payer.publicKey,
+          pdaAccount: pda,
+        })
+        .remainingAccounts([
+          {
+            isSigner: false,
+            isWritable: true,
+            pubkey: playerTokenAccount.address,
+          },
+        ])
+        .signers([payer])
+        .rpc();
+      const gameState = await program.account.gameAccount.fetch(gameAccount);
+      expect(gameState.players.length).to.eq(0);
+      expect(gameState.pendingSettlements[0].held.toString()).to.eq(
+        "200000000000"
+      );
+      const ret = await connection.getTokenAccountBalance(
+        playerTokenAccount.address
+      );
+      expect(ret.value.amount).to.eq("0");
+    });
+  });
+
+  describe("Waitlist", () => {
+    const joinWaitlist = async (
+      gameAccount: PublicKey,
+      mint: PublicKey,
+      payer: Signer,
+      amount: number
+    ) => {
+      const waiting = anchor.web3.Keypair.generate();
+      const airdropTx = await connection.requestAirdrop(
+        waiting.publicKey,
+        2000000000
+      );
+      await connection.confirmTransaction(airdropTx);
+      const waitingTokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        waiting,
+        mint,
+        waiting.publicKey
+      );
+      await mintTo(
+        connection,
+        waiting,
+        mint,
+        waitingTokenAccount.address,
+        payer,
+        amount
+      );
+      const [waitlistAccount] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("waitlist"),
+          gameAccount.toBuffer(),
+          waiting.publicKey.toBuffer(),
+        ],
+        MY_PROGRAM_ID
+      );
+      const escrowTokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        waiting,
+        mint,
+        waitlistAccount,
+        true
+      );
+      await program.methods
+        .joinWaitlist({ amount: new anchor.BN(amount) })
+        .accounts({
+          club: null,
+          mint,
+          gameAccount,
+          waitlistAccount,
+          escrowTokenAccount: escrowTokenAccount.address,
+          playerTokenAccount: waitingTokenAccount.address,
+          player: waiting.publicKey,
+        })
+        .signers([waiting])
+        .rpc();
+      return {
+        waiting,
+        waitingTokenAccount,
+        waitlistAccount,
+        escrowTokenAccount,
+      };
+    };
+
+    it("seats the next waitlisted player when a seat frees up", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame({
+        maxPlayers: 2,
+      });
+      const amount = 150 * Math.pow(10, 9);
+      const seated = [];
+      for (let i = 0; i < 2; i++) {
+        seated.push(
+          await joinGame({
+            amount,
+            gameAccount,
+            mint,
+            payer,
+            tokenAccount: tokenAccount.address,
+          })
+        );
+      }
+      const { waiting, waitlistAccount, escrowTokenAccount } =
+        await joinWaitlist(gameAccount, mint, payer, amount);
+
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      const leaveTable = ({ player, playerTokenAccount }) =>
+        program.methods
+          .leaveTable()
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            mint,
+            gameAccount,
+            gameTokenAccount: tokenAccount.address,
+            playerTokenAccount: playerTokenAccount.address,
+            player: player.publicKey,
+            pdaAccount: pda,
+            handAccount: null,
+          })
+          .signers([player]);
+      const fetchGame = async () => {
+        const { data } = await connection.getAccountInfo(gameAccount);
+        return program.coder.accounts.decode("GameAccount", data);
+      };
+      // without the waitlisted player's accounts the queue is left alone
+      await leaveTable(seated[0]).rpc();
+      let gameState = await fetchGame();
+      expect(gameState.waitlist.length).to.eq(1);
+      expect(gameState.players.length).to.eq(1);
+
+      await leaveTable(seated[1])
+        .remainingAccounts([
+          { pubkey: waitlistAccount, isWritable: true, isSigner: false },
+          {
+            pubkey: escrowTokenAccount.address,
+            isWritable: true,
+            isSigner: false,
+          },
+          { pubkey: waiting.publicKey, isWritable: true, isSigner: false },
+        ])
+        .rpc();
+      gameState = await fetchGame();
+      expect(gameState.waitlist.length).to.eq(0);
+      expect(gameState.players.length).to.eq(1);
+      expect(gameState.players[0].address).to.eql(waiting.publicKey);
+      expect(gameState.players[0].balance.toString()).to.eq(`${amount}`);
+      expect(await connection.getAccountInfo(waitlistAccount)).to.eq(null);
+    });
+
+    it("lets the owner evict a waitlisted player", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame({
+        maxPlayers: 1,
+      });
+      const amount = 150 * Math.pow(10, 9);
+      await joinGame({
+        amount,
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const {
+        waiting,
+        waitingTokenAccount,
+        waitlistAccount,
+        escrowTokenAccount,
+      } = await joinWaitlist(gameAccount, mint, payer, amount);
+      const removeFromWaitlist = (owner: Signer) =>
+        program.methods
+          .removeFromWaitlist()
+          .accounts({
+            club: null,
+            role: null,
+            mint,
+            gameAccount,
+            waitlistAccount,
+            escrowTokenAccount: escrowTokenAccount.address,
+            playerTokenAccount: waitingTokenAccount.address,
+            player: waiting.publicKey,
+            owner: owner.publicKey,
+          })
+          .signers([owner]);
+      await expect(removeFromWaitlist(waiting).rpc()).to.be.rejected;
+      await removeFromWaitlist(payer).rpc();
+      const { data } = await connection.getAccountInfo(gameAccount);
+      const gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.waitlist.length).to.eq(0);
+      const { value } = await connection.getTokenAccountBalance(
+        waitingTokenAccount.address
+      );
+      expect(value.amount).to.eq(`${amount}`);
+    });
   });
 
   describe("Add Chips", () => {
@@ -213,7 +905,7 @@ describe("degods-poker-program", () => {
       const { gameAccount, payer, tokenAccount, mint } = await createGame();
       const { player, playerTokenAccount } = await joinGame({
         amount: 100 * Math.pow(10, 9),
-        gameAccount: gameAccount.publicKey,
+        gameAccount,
         mint,
         payer,
         tokenAccount: tokenAccount.address,
@@ -228,13 +920,19 @@ describe("degods-poker-program", () => {
         100 * Math.pow(10, 9)
       );
       const [pda, bump] = PublicKey.findProgramAddressSync(
-        [gameAccount.publicKey.toBuffer()],
+        [gameAccount.toBuffer()],
         MY_PROGRAM_ID
       );
       await program.methods
-        .addChips({ amount: new anchor.BN(100 * Math.pow(10, 9)) })
+        .addChips({
+          amount: new anchor.BN(100 * Math.pow(10, 9)),
+          topUpToMax: false,
+        })
         .accounts({
-          gameAccount: gameAccount.publicKey,
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint,
+          gameAccount,
           gameTokenAccount: tokenAccount.address,
           player: player.publicKey,
           playerTokenAccount: playerTokenAccount.address,
@@ -247,12 +945,12 @@ describe("degods-poker-program", () => {
     it("happy path add chips and game active", async () => {
       const { gameAccount, payer, tokenAccount, mint } = await createGame();
       const [pda, bump] = PublicKey.findProgramAddressSync(
-        [gameAccount.publicKey.toBuffer()],
+        [gameAccount.toBuffer()],
         MY_PROGRAM_ID
       );
       const { player, playerTokenAccount } = await joinGame({
         amount: 100 * Math.pow(10, 9),
-        gameAccount: gameAccount.publicKey,
+        gameAccount,
         mint,
         payer,
         tokenAccount: tokenAccount.address,
@@ -266,9 +964,15 @@ describe("degods-poker-program", () => {
         100 * Math.pow(10, 9)
       );
       await program.methods
-        .addChips({ amount: new anchor.BN(100 * Math.pow(10, 9)) })
+        .addChips({
+          amount: new anchor.BN(100 * Math.pow(10, 9)),
+          topUpToMax: false,
+        })
         .accounts({
-          gameAccount: gameAccount.publicKey,
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint,
+          gameAccount,
           gameTokenAccount: tokenAccount.address,
           player: player.publicKey,
           playerTokenAccount: playerTokenAccount.address,
@@ -284,7 +988,7 @@ describe("degods-poker-program", () => {
     const { player: player1, playerTokenAccount: playerTokenAccount1 } =
       await joinGame({
         amount: 200 * Math.pow(10, 9),
-        gameAccount: gameAccount.publicKey,
+        gameAccount,
         mint,
         payer,
         tokenAccount: tokenAccount.address,
@@ -292,13 +996,13 @@ describe("degods-poker-program", () => {
     const { player: player2, playerTokenAccount: playerTokenAccount2 } =
       await joinGame({
         amount: 200 * Math.pow(10, 9),
-        gameAccount: gameAccount.publicKey,
+        gameAccount,
         mint,
         payer,
         tokenAccount: tokenAccount.address,
       });
     const [pda, bump] = await PublicKey.findProgramAddressSync(
-      [gameAccount.publicKey.toBuffer()],
+      [gameAccount.toBuffer()],
       MY_PROGRAM_ID
     );
     const tx = await program.methods
@@ -309,7 +1013,11 @@ describe("degods-poker-program", () => {
         ],
       })
       .accounts({
-        gameAccount: gameAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        role: null,
+        mint,
+        gameAccount,
         payer: payer.publicKey,
         pdaAccount: pda,
         tokenAccount: tokenAccount.address,
@@ -329,64 +1037,148 @@ describe("degods-poker-program", () => {
       .signers([payer])
       .rpc();
     console.log(tx);
-    const { data } = await connection.getAccountInfo(gameAccount.publicKey);
+    const { data } = await connection.getAccountInfo(gameAccount);
     const gameState = program.coder.accounts.decode("GameAccount", data);
     expect(gameState.players.length).to.eq(0);
   });
 
-  it("Handles add ons", async () => {
+  it("Keeps unpaid chips on the seat when ejecting", async () => {
     const { gameAccount, tokenAccount, mint, payer } = await createGame();
     const { player, playerTokenAccount } = await joinGame({
-      amount: 100 * Math.pow(10, 9),
-      gameAccount: gameAccount.publicKey,
+      amount: 200 * Math.pow(10, 9),
+      gameAccount,
       mint,
       payer,
       tokenAccount: tokenAccount.address,
     });
-    const [pda, bump] = await PublicKey.findProgramAddressSync(
-      [gameAccount.publicKey.toBuffer()],
+    const [pda] = PublicKey.findProgramAddressSync(
+      [gameAccount.toBuffer()],
       MY_PROGRAM_ID
     );
-    await mintTo(
-      connection,
-      payer,
-      mint,
-      playerTokenAccount.address,
-      payer,
-      200 * Math.pow(10, 9)
-    );
-    await program.methods
-      .addChips({ amount: new anchor.BN(200 * Math.pow(10, 9)) })
-      .accounts({
-        gameAccount: gameAccount.publicKey,
-        gameTokenAccount: tokenAccount.address,
-        player: player.publicKey,
-        playerTokenAccount: playerTokenAccount.address,
+    const eject = (amounts: number[]) =>
+      program.methods
+        .ejectPlayers({
+          amounts: amounts.map((a) => new anchor.BN(a * Math.pow(10, 9))),
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          mint,
+          gameAccount,
+          payer: payer.publicKey,
+          pdaAccount: pda,
+          tokenAccount: tokenAccount.address,
+        })
+        .remainingAccounts([
+          {
+            isSigner: false,
+            isWritable: true,
+            pubkey: playerTokenAccount.address,
+          },
+        ])
+        .signers([payer])
+        .rpc();
+    await expect(eject([201])).to.eventually.rejected;
+    await expect(eject([])).to.eventually.rejected;
+    await expect(eject([100, 100])).to.eventually.rejected;
+    await eject([150]);
+    let game = await program.account.gameAccount.fetch(gameAccount);
+    expect(game.players[0].address.equals(player.publicKey)).to.eq(true);
+    expect(game.players[0].balance.toString()).to.eq("50000000000");
+    await eject([50]);
+    game = await program.account.gameAccount.fetch(gameAccount);
+    expect(game.players.length).to.eq(0);
+  });
+
+  it("Handles add ons", async () => {
+    const { gameAccount, tokenAccount, mint, payer } = await createGame();
+    const { player, playerTokenAccount } = await joinGame({
+      amount: 100 * Math.pow(10, 9),
+      gameAccount,
+      mint,
+      payer,
+      tokenAccount: tokenAccount.address,
+    });
+    const [pda, bump] = await PublicKey.findProgramAddressSync(
+      [gameAccount.toBuffer()],
+      MY_PROGRAM_ID
+    );
+    await mintTo(
+      connection,
+      payer,
+      mint,
+      playerTokenAccount.address,
+      payer,
+      200 * Math.pow(10, 9)
+    );
+    // 100 + 200 would put the stack over the 200 max deposit
+    await expect(
+      program.methods
+        .addChips({
+          amount: new anchor.BN(200 * Math.pow(10, 9)),
+          topUpToMax: false,
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint,
+          gameAccount,
+          gameTokenAccount: tokenAccount.address,
+          player: player.publicKey,
+          playerTokenAccount: playerTokenAccount.address,
+          pdaAccount: pda,
+        })
+        .signers([player])
+        .rpc()
+    ).to.eventually.rejected;
+    await program.methods
+      .addChips({ amount: new anchor.BN(0), topUpToMax: true })
+      .accounts({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        mint,
+        gameAccount,
+        gameTokenAccount: tokenAccount.address,
+        player: player.publicKey,
+        playerTokenAccount: playerTokenAccount.address,
         pdaAccount: pda,
       })
       .signers([player])
       .rpc();
+    const { data } = await connection.getAccountInfo(gameAccount);
+    const gameState = program.coder.accounts.decode("GameAccount", data);
+    expect(gameState.players[0].balance.toString()).to.eq("200000000000");
+    const ret = await connection.getTokenAccountBalance(
+      playerTokenAccount.address
+    );
+    expect(ret.value.amount).to.eq("100000000000");
   });
 
   it("Handles Refunds", async () => {
     const { gameAccount, tokenAccount, mint, payer } = await createGame();
     const { player, playerTokenAccount } = await joinGame({
       amount: 100 * Math.pow(10, 9),
-      gameAccount: gameAccount.publicKey,
+      gameAccount,
       mint,
       payer,
       tokenAccount: tokenAccount.address,
     });
     const [pda, bump] = await PublicKey.findProgramAddressSync(
-      [gameAccount.publicKey.toBuffer()],
+      [gameAccount.toBuffer()],
       MY_PROGRAM_ID
     );
     await program.methods
       .refundPlayer({ amount: new anchor.BN(50 * Math.pow(10, 9)) })
       .accounts({
-        gameAccount: gameAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        role: null,
+        mint,
+        gameAccount,
         gameTokenAccount: tokenAccount.address,
         playerTokenAccount: playerTokenAccount.address,
+        player: player.publicKey,
         pdaAccount: pda,
         payer: payer.publicKey,
       })
@@ -396,17 +1188,168 @@ describe("degods-poker-program", () => {
       playerTokenAccount.address
     );
     expect(ret.value.amount).to.eq("50000000000");
+    const { data } = await connection.getAccountInfo(gameAccount);
+    const gameState = program.coder.accounts.decode("GameAccount", data);
+    expect(gameState.players[0].balance.toString()).to.eq("50000000000");
+    expect(gameState.players[0].withdrawn.toString()).to.eq("50000000000");
   });
 
-  describe("Close Game", () => {
-    it("happy path", async () => {
-      const { gameAccount, payer, tokenAccount, mint } = await createGame();
-      const [pda, bump] = await PublicKey.findProgramAddressSync(
-        [gameAccount.publicKey.toBuffer()],
+  it("Rejects refunds larger than the seat balance", async () => {
+    const { gameAccount, tokenAccount, mint, payer } = await createGame();
+    const { player, playerTokenAccount } = await joinGame({
+      amount: 100 * Math.pow(10, 9),
+      gameAccount,
+      mint,
+      payer,
+      tokenAccount: tokenAccount.address,
+    });
+    const [pda, bump] = await PublicKey.findProgramAddressSync(
+      [gameAccount.toBuffer()],
+      MY_PROGRAM_ID
+    );
+    await expect(
+      program.methods
+        .refundPlayer({ amount: new anchor.BN(100 * Math.pow(10, 9) + 1) })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          mint,
+          gameAccount,
+          gameTokenAccount: tokenAccount.address,
+          playerTokenAccount: playerTokenAccount.address,
+          player: player.publicKey,
+          pdaAccount: pda,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc()
+    ).to.eventually.rejected;
+  });
+
+  describe("Leave Table", () => {
+    it("pays out the seat balance and frees the seat", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 150 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const [pda, bump] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
         MY_PROGRAM_ID
       );
-      await mintTo(connection, payer, mint, tokenAccount.address, payer, 1000);
-      const payerTokenAccount = await createAssociatedTokenAccount(
+      await program.methods
+        .leaveTable()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint,
+          gameAccount,
+          gameTokenAccount: tokenAccount.address,
+          playerTokenAccount: playerTokenAccount.address,
+          player: player.publicKey,
+          pdaAccount: pda,
+          handAccount: null,
+        })
+        .signers([player])
+        .rpc();
+      const { data } = await connection.getAccountInfo(gameAccount);
+      const gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.players.length).to.eq(0);
+      const ret = await connection.getTokenAccountBalance(
+        playerTokenAccount.address
+      );
+      expect(ret.value.amount).to.eq("150000000000");
+    });
+
+    it("is blocked while a hand is in progress", async () => {
+      const { gameAccount, tokenAccount, mint, payer, transactor } =
+        await createGame();
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 150 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const [pda, bump] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      // the transactor has no lamports for the round's rent
+      const shuffleAccount = await commitRound(gameAccount, payer, [player]);
+      await program.methods
+        .startHand()
+        .accounts({
+          shuffleAccount,
+          role: null,
+          gameAccount,
+          payer: transactor.publicKey,
+        })
+        .signers([transactor])
+        .rpc();
+      await expect(
+        program.methods
+          .leaveTable()
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            mint,
+            gameAccount,
+            gameTokenAccount: tokenAccount.address,
+            playerTokenAccount: playerTokenAccount.address,
+            player: player.publicKey,
+            pdaAccount: pda,
+            handAccount: null,
+          })
+          .signers([player])
+          .rpc()
+      ).to.eventually.rejected;
+    });
+  });
+
+  describe("Abandoned Game", () => {
+    it("lets players withdraw once the host goes quiet", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame({
+        inactivityTimeout: new anchor.BN(1),
+      });
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 150 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const [pda, bump] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await sleep(3000);
+      await program.methods
+        .withdrawAbandoned()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint,
+          gameAccount,
+          gameTokenAccount: tokenAccount.address,
+          playerTokenAccount: playerTokenAccount.address,
+          player: player.publicKey,
+          pdaAccount: pda,
+          handAccount: null,
+        })
+        .signers([player])
+        .rpc();
+      const ret = await connection.getTokenAccountBalance(
+        playerTokenAccount.address
+      );
+      expect(ret.value.amount).to.eq("150000000000");
+
+      // the now empty table can be closed by anyone, rent goes to the owner
+      const ownerTokenAccount = await createAssociatedTokenAccount(
         connection,
         payer,
         mint,
@@ -415,20 +1358,1658 @@ describe("degods-poker-program", () => {
       await program.methods
         .closeGame()
         .accounts({
-          gameAccount: gameAccount.publicKey,
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          club: null,
+          mint,
+          gameAccount,
           gameTokenAccount: tokenAccount.address,
-          payer: payer.publicKey,
-          payerTokenAccount,
+          owner: payer.publicKey,
+          payer: player.publicKey,
+          ownerTokenAccount,
+          rakeRecipientTokenAccount: ownerTokenAccount,
+          rakeRecipient: payer.publicKey,
           pdaAccount: pda,
         })
+        .signers([player])
+        .rpc();
+      expect(await connection.getAccountInfo(gameAccount)).to.eq(null);
+    });
+
+    it("calls off a heads-up hand the host walked away from", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame({
+        inactivityTimeout: new anchor.BN(1),
+        maxPlayers: 2,
+        mode: { headsUp: { actionTimeout: new anchor.BN(60) } },
+      });
+      const seated = [];
+      for (let i = 0; i < 2; i++) {
+        seated.push(
+          await joinGame({
+            amount: 200 * Math.pow(10, 9),
+            gameAccount,
+            mint,
+            payer,
+            tokenAccount: tokenAccount.address,
+          })
+        );
+      }
+      const [button] = seated;
+      const shuffleAccount = await commitRound(
+        gameAccount,
+        payer,
+        seated.map(({ player }) => player)
+      );
+      const handAccount = findHand(gameAccount);
+      await program.methods
+        .dealHand({
+          cardCommitments: new Array(9).fill(new Array(32).fill(0)),
+          sealedHoleCards: [new Array(128).fill(0), new Array(128).fill(0)],
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          handAccount,
+          shuffleAccount,
+          payer: payer.publicKey,
+          role: null,
+        })
         .signers([payer])
         .rpc();
-      const ret = await connection.getAccountInfo(gameAccount.publicKey);
-      const ret2 = await connection.getAccountInfo(tokenAccount.address);
-      expect(ret).to.eq(null);
-      expect(ret2).to.eq(null);
-      const ret3 = await connection.getTokenAccountBalance(payerTokenAccount);
-      expect(ret3.value.amount).to.eq("1000");
+      await program.methods
+        .playerAction({
+          action: { raiseTo: { 0: new anchor.BN(50 * Math.pow(10, 9)) } },
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          handAccount,
+          payer: button.player.publicKey,
+          role: null,
+        })
+        .signers([button.player])
+        .rpc();
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await sleep(3000);
+      const withdraw = (
+        { player, playerTokenAccount },
+        hand: PublicKey | null
+      ) =>
+        program.methods
+          .withdrawAbandoned()
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            mint,
+            gameAccount,
+            gameTokenAccount: tokenAccount.address,
+            playerTokenAccount: playerTokenAccount.address,
+            player: player.publicKey,
+            pdaAccount: pda,
+            handAccount: hand,
+          })
+          .signers([player])
+          .rpc();
+      // the hand has to be called off before anyone leaves
+      await expect(withdraw(button, null)).to.eventually.rejected;
+      await withdraw(button, handAccount);
+      await withdraw(seated[1], null);
+      for (const { playerTokenAccount } of seated) {
+        const ret = await connection.getTokenAccountBalance(
+          playerTokenAccount.address
+        );
+        expect(ret.value.amount).to.eq("200000000000");
+      }
+    });
+
+    it("refuses withdrawals while the host is active", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 150 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const [pda, bump] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await expect(
+        program.methods
+          .withdrawAbandoned()
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            mint,
+            gameAccount,
+            gameTokenAccount: tokenAccount.address,
+            playerTokenAccount: playerTokenAccount.address,
+            player: player.publicKey,
+            pdaAccount: pda,
+            handAccount: null,
+          })
+          .signers([player])
+          .rpc()
+      ).to.eventually.rejected;
+    });
+  });
+
+  describe("Settle Hand", () => {
+    it("applies zero-sum deltas and rake to seat balances", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame({
+        rakeBps: 500,
+        rakeCap: new anchor.BN(10 * Math.pow(10, 9)),
+      });
+      const { player: winner } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const { player: loser } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      await beginHand(gameAccount, payer, [winner, loser]);
+      await program.methods
+        .settleHand({
+          deltas: [
+            {
+              player: winner.publicKey,
+              delta: new anchor.BN(95 * Math.pow(10, 9)),
+            },
+            {
+              player: loser.publicKey,
+              delta: new anchor.BN(-100 * Math.pow(10, 9)),
+            },
+          ],
+          rake: new anchor.BN(5 * Math.pow(10, 9)),
+          handHash: null,
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          gameAccount,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      const { data } = await connection.getAccountInfo(gameAccount);
+      const gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.players[0].balance.toString()).to.eq("295000000000");
+      expect(gameState.players[1].balance.toString()).to.eq("100000000000");
+      expect(gameState.rakeAccrued.toString()).to.eq("5000000000");
+    });
+
+    it("chains hand hashes onto the table's history", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const first = createHash("sha256").update("hand 1").digest();
+      const second = createHash("sha256").update("hand 2").digest();
+      await beginHand(gameAccount, payer, [player]);
+      await program.methods
+        .settleHand({
+          deltas: [{ player: player.publicKey, delta: new anchor.BN(0) }],
+          rake: new anchor.BN(0),
+          handHash: [...first],
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          gameAccount,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      await program.methods
+        .commitHandHistory({ handHash: [...second] })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          gameAccount,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      let expected = Buffer.alloc(32);
+      for (const hash of [first, second]) {
+        expected = createHash("sha256").update(expected).update(hash).digest();
+      }
+      const gameState = await program.account.gameAccount.fetch(gameAccount);
+      expect(Buffer.from(gameState.handHistory)).to.eql(expected);
+      expect(gameState.handsCommitted.toString()).to.eq("2");
+    });
+
+    it("rejects deltas that do not sum to zero", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const settle = (delta: number) =>
+        program.methods
+          .settleHand({
+            deltas: [{ player: player.publicKey, delta: new anchor.BN(delta) }],
+            rake: new anchor.BN(0),
+            handHash: null,
+          })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            role: null,
+            gameAccount,
+            payer: payer.publicKey,
+          })
+          .signers([payer])
+          .rpc();
+      // only a hand started from a shuffle round can be settled
+      await expect(settle(0)).to.be.rejectedWith(/NoHandInProgress/);
+      await beginHand(gameAccount, payer, [player]);
+      await expect(settle(1)).to.eventually.rejected;
+      await settle(0);
+    });
+  });
+
+  describe("Transactor", () => {
+    it("settles hands but cannot rotate itself", async () => {
+      const { gameAccount, tokenAccount, mint, payer, transactor } =
+        await createGame();
+      const { player } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      await beginHand(gameAccount, payer, [player]);
+      await program.methods
+        .settleHand({
+          deltas: [{ player: player.publicKey, delta: new anchor.BN(0) }],
+          rake: new anchor.BN(0),
+          handHash: null,
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          gameAccount,
+          payer: transactor.publicKey,
+        })
+        .signers([transactor])
+        .rpc();
+      await expect(
+        program.methods
+          .setGameTransactor({ transactor: transactor.publicKey })
+          .accounts({
+            role: null,
+            club: null,
+            gameAccount,
+            owner: transactor.publicKey,
+          })
+          .signers([transactor])
+          .rpc()
+      ).to.eventually.rejected;
+    });
+
+    it("owner rotates the transactor", async () => {
+      const { gameAccount, payer } = await createGame();
+      const next = anchor.web3.Keypair.generate();
+      await program.methods
+        .setGameTransactor({ transactor: next.publicKey })
+        .accounts({
+          role: null,
+          club: null,
+          gameAccount,
+          owner: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      const { data } = await connection.getAccountInfo(gameAccount);
+      const gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.transactor).to.eql(next.publicKey);
+    });
+  });
+
+  describe("Rake", () => {
+    it("collects capped rake into rake_accrued and withdraws it", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame({
+        rakeBps: 500,
+        rakeCap: new anchor.BN(3 * Math.pow(10, 9)),
+      });
+      const { player } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const [pda, bump] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      // 5% of 100 would be 5, the cap keeps it at 3
+      await program.methods
+        .collectRake({
+          player: player.publicKey,
+          pot: new anchor.BN(100 * Math.pow(10, 9)),
+        })
+        .accounts({
+          role: null,
+          gameAccount,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      let { data } = await connection.getAccountInfo(gameAccount);
+      let gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.rakeAccrued.toString()).to.eq("3000000000");
+      expect(gameState.players[0].balance.toString()).to.eq("197000000000");
+
+      const payerTokenAccount = await createAssociatedTokenAccount(
+        connection,
+        payer,
+        mint,
+        payer.publicKey
+      );
+      await program.methods
+        .withdrawRake()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          club: null,
+          mint,
+          gameAccount,
+          gameTokenAccount: tokenAccount.address,
+          rakeRecipientTokenAccount: payerTokenAccount,
+          rakeRecipient: payer.publicKey,
+          pdaAccount: pda,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      ({ data } = await connection.getAccountInfo(gameAccount));
+      gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.rakeAccrued.toString()).to.eq("0");
+      const ret = await connection.getTokenAccountBalance(payerTokenAccount);
+      expect(ret.value.amount).to.eq("3000000000");
+    });
+  });
+
+  describe("Close Game", () => {
+    it("happy path", async () => {
+      const { gameAccount, payer, tokenAccount, mint } = await createGame();
+      const [pda, bump] = await PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await mintTo(connection, payer, mint, tokenAccount.address, payer, 1000);
+      const payerTokenAccount = await createAssociatedTokenAccount(
+        connection,
+        payer,
+        mint,
+        payer.publicKey
+      );
+      await program.methods
+        .closeGame()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          club: null,
+          mint,
+          gameAccount,
+          gameTokenAccount: tokenAccount.address,
+          owner: payer.publicKey,
+          payer: payer.publicKey,
+          ownerTokenAccount: payerTokenAccount,
+          rakeRecipientTokenAccount: payerTokenAccount,
+          rakeRecipient: payer.publicKey,
+          pdaAccount: pda,
+        })
+        .signers([payer])
+        .rpc();
+      const ret = await connection.getAccountInfo(gameAccount);
+      const ret2 = await connection.getAccountInfo(tokenAccount.address);
+      expect(ret).to.eq(null);
+      expect(ret2).to.eq(null);
+      const ret3 = await connection.getTokenAccountBalance(payerTokenAccount);
+      expect(ret3.value.amount).to.eq("1000");
+    });
+  });
+
+  describe("Token-2022", () => {
+    it("Credits seats with what the vault receives after transfer fees", async () => {
+      // 1% transfer fee
+      const { gameAccount, tokenAccount, mint, payer } = await createGame(
+        {},
+        100
+      );
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 150 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const vault = await connection.getTokenAccountBalance(
+        tokenAccount.address
+      );
+      expect(vault.value.amount).to.eq("148500000000");
+      const { data } = await connection.getAccountInfo(gameAccount);
+      const gameState = program.coder.accounts.decode("GameAccount", data);
+      expect(gameState.players[0].balance.toString()).to.eq("148500000000");
+      expect(gameState.players[0].deposited.toString()).to.eq("148500000000");
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await program.methods
+        .leaveTable()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint,
+          gameAccount,
+          gameTokenAccount: tokenAccount.address,
+          playerTokenAccount: playerTokenAccount.address,
+          player: player.publicKey,
+          pdaAccount: pda,
+          handAccount: null,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([player])
+        .rpc();
+      const ret = await connection.getTokenAccountBalance(
+        playerTokenAccount.address
+      );
+      expect(ret.value.amount).to.eq("147015000000");
+    });
+  });
+
+  describe("Native SOL", () => {
+    it("Takes buy-ins and pays out in lamports", async () => {
+      const payer = anchor.web3.Keypair.generate();
+      const player = anchor.web3.Keypair.generate();
+      for (const wallet of [payer, player]) {
+        const airdropTx = await connection.requestAirdrop(
+          wallet.publicKey,
+          2000000000
+        );
+        await connection.confirmTransaction(airdropTx);
+      }
+      const gameAccount = findGameAccount(payer.publicKey, 0);
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await program.methods
+        .createCashGame({
+          maxDeposit: new anchor.BN(1000000000),
+          maxPlayers: 8,
+          minDeposit: new anchor.BN(500000000),
+          tokenMint: NATIVE_MINT,
+          rakeBps: 0,
+          rakeCap: new anchor.BN(0),
+          rakeRecipient: payer.publicKey,
+          inactivityTimeout: new anchor.BN(3600),
+          smallBlind: new anchor.BN(5000000),
+          bigBlind: new anchor.BN(10000000),
+          ante: new anchor.BN(0),
+          variant: { nlhe: {} },
+          tableName: "SOL NLHE",
+          minBuyInBb: 0,
+          maxBuyInBb: 0,
+          maxSitOutSeconds: new anchor.BN(0),
+          mode: { dealer: {} },
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          club: null,
+          hostRegistry: findHostRegistry(payer.publicKey),
+          gameAccount,
+          pdaAccount: pda,
+          payer: payer.publicKey,
+          transactor: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      const reserve = await connection.getMinimumBalanceForRentExemption(0);
+      expect(await connection.getBalance(pda)).to.eq(reserve);
+      await program.methods
+        .joinGame({ amount: new anchor.BN(500000000), seat: 0 })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          club: null,
+          mint: null,
+          gameAccount,
+          gameTokenAccount: null,
+          playerTokenAccount: null,
+          player: player.publicKey,
+          pdaAccount: pda,
+        })
+        .signers([player])
+        .rpc();
+      expect(await connection.getBalance(pda)).to.eq(reserve + 500000000);
+      const before = await connection.getBalance(player.publicKey);
+      await program.methods
+        .leaveTable()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint: null,
+          gameAccount,
+          gameTokenAccount: null,
+          playerTokenAccount: null,
+          player: player.publicKey,
+          pdaAccount: pda,
+          handAccount: null,
+        })
+        .signers([player])
+        .rpc();
+      // the player pays the transaction fee out of the same wallet
+      const after = await connection.getBalance(player.publicKey);
+      expect(after - before).to.be.greaterThan(500000000 - 10000);
+      expect(await connection.getBalance(pda)).to.eq(reserve);
+      await program.methods
+        .closeGame()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          club: null,
+          mint: null,
+          gameAccount,
+          gameTokenAccount: null,
+          ownerTokenAccount: null,
+          rakeRecipientTokenAccount: null,
+          rakeRecipient: payer.publicKey,
+          pdaAccount: pda,
+          owner: payer.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      expect(await connection.getBalance(pda)).to.eq(0);
+    });
+
+    it("Rejects lamport buy-ins on a token table", async () => {
+      const { gameAccount } = await createGame();
+      const player = anchor.web3.Keypair.generate();
+      const airdropTx = await connection.requestAirdrop(
+        player.publicKey,
+        2000000000
+      );
+      await connection.confirmTransaction(airdropTx);
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await expect(
+        program.methods
+          .joinGame({ amount: new anchor.BN(100 * Math.pow(10, 9)), seat: 0 })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            club: null,
+            mint: null,
+            gameAccount,
+            gameTokenAccount: null,
+            playerTokenAccount: null,
+            player: player.publicKey,
+            pdaAccount: pda,
+          })
+          .signers([player])
+          .rpc()
+      ).to.eventually.rejected;
+    });
+  });
+
+  describe("Clubs", () => {
+    it("Creates tables from club defaults and lets admins manage them", async () => {
+      const owner = anchor.web3.Keypair.generate();
+      const admin = anchor.web3.Keypair.generate();
+      for (const wallet of [owner, admin]) {
+        const airdropTx = await connection.requestAirdrop(
+          wallet.publicKey,
+          2000000000
+        );
+        await connection.confirmTransaction(airdropTx);
+      }
+      const mint = await createMint(
+        connection,
+        owner,
+        owner.publicKey,
+        null,
+        9
+      );
+      const club = findClub(owner.publicKey, "degods");
+      await program.methods
+        .createClub("degods", {
+          defaultRakeBps: 500,
+          defaultRakeCap: new anchor.BN(Math.pow(10, 9)),
+          defaultRakeRecipient: owner.publicKey,
+          defaultTokenMint: mint,
+        })
+        .accounts({ club, owner: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .setClubAdmins({ members: [admin.publicKey] })
+        .accounts({ club, owner: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const gameAccount = findGameAccount(admin.publicKey, 0);
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      const tokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        admin,
+        mint,
+        pda,
+        true
+      );
+      await program.methods
+        .createCashGame({
+          maxDeposit: new anchor.BN(200 * Math.pow(10, 9)),
+          maxPlayers: 8,
+          minDeposit: new anchor.BN(100 * Math.pow(10, 9)),
+          tokenMint: PublicKey.default,
+          rakeBps: 0,
+          rakeCap: new anchor.BN(0),
+          rakeRecipient: PublicKey.default,
+          inactivityTimeout: new anchor.BN(3600),
+          smallBlind: new anchor.BN(1 * Math.pow(10, 9)),
+          bigBlind: new anchor.BN(2 * Math.pow(10, 9)),
+          ante: new anchor.BN(0),
+          variant: { nlhe: {} },
+          tableName: "Club NLHE",
+          minBuyInBb: 0,
+          maxBuyInBb: 0,
+          maxSitOutSeconds: new anchor.BN(0),
+          mode: { dealer: {} },
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          club,
+          hostRegistry: findHostRegistry(admin.publicKey),
+          gameAccount,
+          pdaAccount: pda,
+          payer: admin.publicKey,
+          transactor: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+      let gameState = await program.account.gameAccount.fetch(gameAccount);
+      expect(gameState.club).to.eql(club);
+      expect(gameState.tokenMint).to.eql(mint);
+      expect(gameState.rakeBps).to.eq(500);
+      expect(gameState.rakeRecipient).to.eql(owner.publicKey);
+
+      // the club owner didn't create the table but can still pause it
+      await program.methods
+        .pauseGame()
+        .accounts({
+          gameAccount,
+          owner: owner.publicKey,
+          club,
+          role: null,
+          program: MY_PROGRAM_ID,
+          eventAuthority,
+        })
+        .signers([owner])
+        .rpc();
+      gameState = await program.account.gameAccount.fetch(gameAccount);
+      expect(gameState.status).to.eql({ paused: {} });
+      await expect(
+        program.methods
+          .resumeGame()
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            gameAccount,
+            owner: owner.publicKey,
+            club: null,
+            role: null,
+          })
+          .signers([owner])
+          .rpc()
+      ).to.eventually.rejected;
+      await program.methods
+        .resumeGame()
+        .accounts({
+          gameAccount,
+          owner: owner.publicKey,
+          club,
+          role: null,
+          program: MY_PROGRAM_ID,
+          eventAuthority,
+        })
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .setClubAllowlist({ members: [admin.publicKey] })
+        .accounts({ club, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      await expect(
+        joinGame({
+          amount: 100 * Math.pow(10, 9),
+          gameAccount,
+          mint,
+          payer: owner,
+          tokenAccount: tokenAccount.address,
+          club,
+        })
+      ).to.eventually.rejected;
+    });
+  });
+
+  describe("Events", () => {
+    it("emits status changes through a self-CPI", async () => {
+      const { gameAccount, payer } = await createGame();
+      const signature = await program.methods
+        .pauseGame()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          owner: payer.publicKey,
+          club: null,
+          role: null,
+        })
+        .signers([payer])
+        .rpc({ commitment: "confirmed" });
+      const tx = await connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const [ix] = tx.meta.innerInstructions[0].instructions;
+      // the first 8 bytes are anchor's event instruction tag
+      const data = anchor.utils.bytes.bs58.decode(ix.data).subarray(8);
+      const event = program.coder.events.decode(
+        anchor.utils.bytes.base64.encode(Buffer.from(data))
+      );
+      expect(event.name).to.eq("GameStatusChanged");
+      expect(event.data.game).to.eql(gameAccount);
+      expect(event.data.status).to.eql({ paused: {} });
+    });
+  });
+
+  describe("Shuffle", () => {
+    it("derives the deck seed once every party has revealed", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const shuffleAccount = findShuffle(gameAccount);
+      const dealerSeed = anchor.web3.Keypair.generate().publicKey.toBuffer();
+      const playerSeed = anchor.web3.Keypair.generate().publicKey.toBuffer();
+      const sha256 = (data: Buffer) =>
+        createHash("sha256").update(data).digest();
+      const seedAccounts = (party: PublicKey) => ({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        gameAccount,
+        shuffleAccount,
+        party,
+        handAccount: null,
+      });
+
+      await program.methods
+        .openShuffle({ value: [...sha256(dealerSeed)] })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          shuffleAccount,
+          payer: payer.publicKey,
+          role: null,
+        })
+        .signers([payer])
+        .rpc();
+      const startHand = () =>
+        program.methods
+          .startHand()
+          .accounts({
+            shuffleAccount,
+            role: null,
+            gameAccount,
+            payer: payer.publicKey,
+          })
+          .signers([payer])
+          .rpc();
+      // the seated player hasn't committed yet
+      await expect(startHand()).to.eventually.rejected;
+      await program.methods
+        .commitShuffleSeed({ value: [...sha256(playerSeed)] })
+        .accounts(seedAccounts(player.publicKey))
+        .signers([player])
+        .rpc();
+      await startHand();
+      // no reveals while the hand is being played
+      await expect(
+        program.methods
+          .revealShuffleSeed({ value: [...playerSeed] })
+          .accounts(seedAccounts(player.publicKey))
+          .signers([player])
+          .rpc()
+      ).to.eventually.rejected;
+      await program.methods
+        .settleHand({
+          deltas: [{ player: player.publicKey, delta: new anchor.BN(0) }],
+          rake: new anchor.BN(0),
+          handHash: null,
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          gameAccount,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+
+      await expect(
+        program.methods
+          .revealShuffleSeed({ value: [...dealerSeed] })
+          .accounts(seedAccounts(player.publicKey))
+          .signers([player])
+          .rpc()
+      ).to.eventually.rejected;
+      await program.methods
+        .revealShuffleSeed({ value: [...playerSeed] })
+        .accounts(seedAccounts(player.publicKey))
+        .signers([player])
+        .rpc();
+      await program.methods
+        .revealShuffleSeed({ value: [...dealerSeed] })
+        .accounts(seedAccounts(payer.publicKey))
+        .signers([payer])
+        .rpc();
+      const shuffle = await program.account.shuffleAccount.fetch(
+        shuffleAccount
+      );
+      expect(shuffle.phase).to.eql({ revealed: {} });
+      expect(Buffer.from(shuffle.deckSeed)).to.eql(
+        sha256(Buffer.concat([dealerSeed, playerSeed]))
+      );
+    });
+
+    it("only replaces a round once every seed is in", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const shuffleAccount = findShuffle(gameAccount);
+      const dealerSeed = anchor.web3.Keypair.generate().publicKey.toBuffer();
+      const playerSeed = anchor.web3.Keypair.generate().publicKey.toBuffer();
+      const sha256 = (data: Buffer) =>
+        createHash("sha256").update(data).digest();
+      const open = (seed: Buffer) =>
+        program.methods
+          .openShuffle({ value: [...sha256(seed)] })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            gameAccount,
+            shuffleAccount,
+            payer: payer.publicKey,
+            role: null,
+          })
+          .signers([payer])
+          .rpc();
+      const seedAccounts = (party: PublicKey) => ({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        gameAccount,
+        shuffleAccount,
+        party,
+        handAccount: null,
+      });
+
+      await open(dealerSeed);
+      await program.methods
+        .commitShuffleSeed({ value: [...sha256(playerSeed)] })
+        .accounts(seedAccounts(player.publicKey))
+        .signers([player])
+        .rpc();
+      // the round is still taking commitments
+      await expect(open(dealerSeed)).to.eventually.rejected;
+      await program.methods
+        .startHand()
+        .accounts({
+          shuffleAccount,
+          role: null,
+          gameAccount,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      await program.methods
+        .settleHand({
+          deltas: [{ player: player.publicKey, delta: new anchor.BN(0) }],
+          rake: new anchor.BN(0),
+          handHash: null,
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          gameAccount,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      // the dealer hasn't revealed its own seed
+      await expect(open(dealerSeed)).to.eventually.rejected;
+      await program.methods
+        .revealShuffleSeed({ value: [...dealerSeed] })
+        .accounts(seedAccounts(payer.publicKey))
+        .signers([payer])
+        .rpc();
+      // the player still has time to reveal
+      await expect(open(dealerSeed)).to.eventually.rejected;
+      await program.methods
+        .revealShuffleSeed({ value: [...playerSeed] })
+        .accounts(seedAccounts(player.publicKey))
+        .signers([player])
+        .rpc();
+      await open(dealerSeed);
+      const shuffle = await program.account.shuffleAccount.fetch(
+        shuffleAccount
+      );
+      expect(shuffle.hand.toNumber()).to.eq(2);
+      expect(shuffle.phase).to.eql({ committing: {} });
+      expect(shuffle.parties.length).to.eq(1);
+    });
+  });
+
+  describe("Heads-Up", () => {
+    const sha256 = (data: Buffer) =>
+      createHash("sha256").update(data).digest();
+    // the dealer's seed, then each player's in the order they commit
+    const seeds = [4, 1, 2].map((byte) => Buffer.alloc(32, byte));
+    // button 5c 2h, big blind Jd Ts, board 6c Tc 2d 7h 2c
+    const deck = permutation(sha256(Buffer.concat(seeds)), 52);
+
+    const setup = async (dealt: number[]) => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame({
+        maxPlayers: 2,
+        mode: { headsUp: { actionTimeout: new anchor.BN(60) } },
+      });
+      const seated: Signer[] = [];
+      for (let i = 0; i < 2; i++) {
+        const { player } = await joinGame({
+          amount: 200 * Math.pow(10, 9),
+          gameAccount,
+          mint,
+          payer,
+          tokenAccount: tokenAccount.address,
+        });
+        seated.push(player);
+      }
+      const shuffleAccount = findShuffle(gameAccount);
+      const handAccount = findHand(gameAccount);
+      // the hand account only exists once the first hand is dealt
+      const seedAccounts = (party: PublicKey, hand: PublicKey | null) => ({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        gameAccount,
+        shuffleAccount,
+        party,
+        handAccount: hand,
+      });
+
+      await program.methods
+        .openShuffle({ value: [...sha256(seeds[0])] })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          shuffleAccount,
+          payer: payer.publicKey,
+          role: null,
+        })
+        .signers([payer])
+        .rpc();
+      for (const [i, player] of seated.entries()) {
+        await program.methods
+          .commitShuffleSeed({ value: [...sha256(seeds[i + 1])] })
+          .accounts(seedAccounts(player.publicKey, null))
+          .signers([player])
+          .rpc();
+      }
+
+      const cards = dealt.map((card) => ({
+        card,
+        salt: [...anchor.web3.Keypair.generate().publicKey.toBuffer()],
+      }));
+      const commit = ({ card, salt }) =>
+        [...sha256(Buffer.concat([Buffer.from([card]), Buffer.from(salt)]))];
+      await program.methods
+        .dealHand({
+          cardCommitments: cards.map(commit),
+          sealedHoleCards: [new Array(128).fill(0), new Array(128).fill(0)],
+        })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          gameAccount,
+          handAccount,
+          shuffleAccount,
+          payer: payer.publicKey,
+          role: null,
+        })
+        .signers([payer])
+        .rpc();
+
+      const act = (player: Signer, action: object) =>
+        program.methods
+          .playerAction({ action })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            gameAccount,
+            handAccount,
+            payer: player.publicKey,
+            role: null,
+          })
+          .signers([player])
+          .rpc();
+      const revealBoard = (street: typeof cards) =>
+        program.methods
+          .revealBoard({ cards: street })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            gameAccount,
+            handAccount,
+            payer: payer.publicKey,
+            role: null,
+          })
+          .signers([payer])
+          .rpc();
+      const revealSeeds = async () => {
+        for (const [i, party] of [payer, ...seated].entries()) {
+          await program.methods
+            .revealShuffleSeed({ value: [...seeds[i]] })
+            .accounts(seedAccounts(party.publicKey, handAccount))
+            .signers([party])
+            .rpc();
+        }
+      };
+      const settle = () =>
+        program.methods
+          .settleHeadsUpHand()
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            gameAccount,
+            handAccount,
+            shuffleAccount,
+            payer: payer.publicKey,
+          })
+          .signers([payer])
+          .rpc();
+      const balances = async () => {
+        const game = await program.account.gameAccount.fetch(gameAccount);
+        expect(game.handInProgress).to.eq(false);
+        return seated.map((player) =>
+          game.players
+            .find((p) => p.address.equals(player.publicKey))
+            .balance.toNumber()
+        );
+      };
+      return {
+        gameAccount,
+        handAccount,
+        seated,
+        cards,
+        act,
+        revealBoard,
+        revealSeeds,
+        settle,
+        balances,
+      };
+    };
+
+    it("plays a hand through to showdown", async () => {
+      const {
+        gameAccount,
+        handAccount,
+        seated,
+        cards,
+        act,
+        revealBoard,
+        revealSeeds,
+        settle,
+        balances,
+      } = await setup(deck.slice(0, 9));
+      const [button, bigBlind] = seated;
+      // the big blind can't act before the button preflop
+      await expect(act(bigBlind, { check: {} })).to.eventually.rejected;
+      await act(button, { call: {} });
+      await act(bigBlind, { check: {} });
+      const streets = [cards.slice(4, 7), cards.slice(7, 8), cards.slice(8)];
+      for (const street of streets) {
+        await revealBoard(street);
+        await act(bigBlind, { check: {} });
+        await act(button, { check: {} });
+      }
+
+      let hand = await program.account.handAccount.fetch(handAccount);
+      expect(hand.street).to.eql({ showdown: {} });
+      for (const [i, player] of seated.entries()) {
+        await program.methods
+          .revealHoleCards({
+            player: player.publicKey,
+            cards: cards.slice(i * 2, i * 2 + 2),
+          })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            gameAccount,
+            handAccount,
+            payer: player.publicKey,
+            role: null,
+          })
+          .signers([player])
+          .rpc();
+      }
+      hand = await program.account.handAccount.fetch(handAccount);
+      expect(hand.outcome).to.eql({ winner: { 0: 0 } });
+      // nothing is paid until the seeds show the cards came off the deck
+      await expect(settle()).to.eventually.rejected;
+      await revealSeeds();
+      await settle();
+      expect(await balances()).to.eql([
+        202 * Math.pow(10, 9),
+        198 * Math.pow(10, 9),
+      ]);
+    });
+
+    it("voids a hand dealt off the deck", async () => {
+      const { seated, cards, act, revealBoard, revealSeeds, settle, balances } =
+        await setup([48, 49, 0, 21, 44, 41, 30, 11, 4]);
+      const [button, bigBlind] = seated;
+      await act(button, { call: {} });
+      await act(bigBlind, { check: {} });
+      await revealBoard(cards.slice(4, 7));
+      await act(bigBlind, {
+        raiseTo: { 0: new anchor.BN(4 * Math.pow(10, 9)) },
+      });
+      await act(button, { fold: {} });
+      await revealSeeds();
+      await settle();
+      // the flop wasn't the deck's, so the pot goes back
+      expect(await balances()).to.eql([
+        200 * Math.pow(10, 9),
+        200 * Math.pow(10, 9),
+      ]);
+    });
+  });
+
+  describe("Disputes", () => {
+    const setup = async (challengeWindow: number) => {
+      const game = await createGame();
+      const { gameAccount, tokenAccount, mint, payer } = game;
+      const arbiter = anchor.web3.Keypair.generate();
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await program.methods
+        .setDisputeSettings({
+          challengeWindow: new anchor.BN(challengeWindow),
+          arbiter: arbiter.publicKey,
+        })
+        .accounts({
+          club: null,
+          role: null,
+          gameAccount,
+          owner: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      await program.methods
+        .ejectPlayers({ amounts: [new anchor.BN(150 * Math.pow(10, 9))] })
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          role: null,
+          mint,
+          gameAccount,
+          payer: payer.publicKey,
+          pdaAccount: pda,
+          tokenAccount: tokenAccount.address,
+        })
+        .remainingAccounts([
+          {
+            isSigner: false,
+            isWritable: true,
+            pubkey: playerTokenAccount.address,
+          },
+        ])
+        .signers([payer])
+        .rpc();
+      const payAccounts = (payer: PublicKey) => ({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        mint,
+        gameAccount,
+        gameTokenAccount: tokenAccount.address,
+        playerTokenAccount: playerTokenAccount.address,
+        player: player.publicKey,
+        payer,
+        pdaAccount: pda,
+      });
+      const playerBalance = async () =>
+        (await connection.getTokenAccountBalance(playerTokenAccount.address))
+          .value.amount;
+      // the player signs their claim, anyone can submit it
+      const dispute = async (claimed: anchor.BN, evidence: number[]) => {
+        const { pendingSettlements } = await program.account.gameAccount.fetch(
+          gameAccount
+        );
+        const message = Buffer.concat([
+          Buffer.from("degods-poker:dispute"),
+          gameAccount.toBuffer(),
+          pendingSettlements[0].nonce.toArrayLike(Buffer, "le", 8),
+          claimed.toArrayLike(Buffer, "le", 8),
+          Buffer.from(evidence),
+        ]);
+        return program.methods
+          .disputeSettlement({ claimed, evidence })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            gameAccount,
+            player: player.publicKey,
+            payer: payer.publicKey,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .preInstructions([
+            Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: player.secretKey,
+              message,
+            }),
+          ])
+          .signers([payer])
+          .rpc();
+      };
+      return { ...game, arbiter, player, payAccounts, playerBalance, dispute };
+    };
+
+    it("holds payouts until the challenge window passes", async () => {
+      const { gameAccount, payer, payAccounts, playerBalance, dispute } =
+        await setup(2);
+      const gameState = await program.account.gameAccount.fetch(gameAccount);
+      // the 50 not paid out stays on the seat
+      expect(gameState.players[0].balance.toString()).to.eq("50000000000");
+      expect(gameState.pendingSettlements[0].held.toString()).to.eq(
+        "150000000000"
+      );
+      expect(await playerBalance()).to.eq("0");
+      const release = () =>
+        program.methods
+          .releaseSettlement()
+          .accounts(payAccounts(payer.publicKey))
+          .signers([payer])
+          .rpc();
+      await expect(release()).to.eventually.rejected;
+      await sleep(3000);
+      // too late to dispute now
+      await expect(
+        dispute(new anchor.BN(200 * Math.pow(10, 9)), new Array(32).fill(1))
+      ).to.eventually.rejected;
+      await release();
+      expect(await playerBalance()).to.eq("150000000000");
+      const { pendingSettlements } = await program.account.gameAccount.fetch(
+        gameAccount
+      );
+      expect(pendingSettlements.length).to.eq(0);
+    });
+
+    it("freezes a disputed payout until the arbiter rules", async () => {
+      const {
+        gameAccount,
+        payer,
+        arbiter,
+        payAccounts,
+        playerBalance,
+        dispute,
+      } = await setup(60);
+      const evidence = [
+        ...createHash("sha256").update("signed hand record").digest(),
+      ];
+      // a claim the player didn't sign doesn't count
+      await expect(
+        program.methods
+          .disputeSettlement({
+            claimed: new anchor.BN(200 * Math.pow(10, 9)),
+            evidence,
+          })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            gameAccount,
+            player: arbiter.publicKey,
+            payer: payer.publicKey,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([payer])
+          .rpc()
+      ).to.eventually.rejected;
+      await dispute(new anchor.BN(200 * Math.pow(10, 9)), evidence);
+      const { pendingSettlements } = await program.account.gameAccount.fetch(
+        gameAccount
+      );
+      expect(pendingSettlements[0].disputed).to.eq(true);
+      expect(pendingSettlements[0].evidence).to.eql(evidence);
+      // the arbiter now has until the deadline to rule
+      await expect(
+        program.methods
+          .releaseSettlement()
+          .accounts(payAccounts(payer.publicKey))
+          .signers([payer])
+          .rpc()
+      ).to.eventually.rejected;
+
+      const resolve = (signer: Signer, amount: number) =>
+        program.methods
+          .resolveDispute({ amount: new anchor.BN(amount * Math.pow(10, 9)) })
+          .accounts(payAccounts(signer.publicKey))
+          .signers([signer])
+          .rpc();
+      // only the arbiter rules, and never beyond what was held back
+      await expect(resolve(payer, 150)).to.eventually.rejected;
+      await expect(resolve(arbiter, 151)).to.eventually.rejected;
+      await resolve(arbiter, 100);
+      expect(await playerBalance()).to.eq("100000000000");
+      // what was held but not paid goes back on the seat
+      const gameState = await program.account.gameAccount.fetch(gameAccount);
+      expect(gameState.players[0].balance.toString()).to.eq("100000000000");
+      expect(gameState.pendingSettlements.length).to.eq(0);
+    });
+  });
+
+  describe("Signed cash-outs", () => {
+    it("pays out on a statement signed by the player and the table", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 200 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      await program.methods
+        .setSignedCashOuts({ required: true })
+        .accounts({
+          club: null,
+          role: null,
+          gameAccount,
+          owner: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      // the owner can no longer decide payouts alone
+      await expect(
+        program.methods
+          .ejectPlayers({ amounts: [new anchor.BN(0)] })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            role: null,
+            mint,
+            gameAccount,
+            payer: payer.publicKey,
+            pdaAccount: pda,
+            tokenAccount: tokenAccount.address,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: true,
+              pubkey: playerTokenAccount.address,
+            },
+          ])
+          .signers([payer])
+          .rpc()
+      ).to.eventually.rejected;
+
+      const { players } = await program.account.gameAccount.fetch(gameAccount);
+      const statement = {
+        seat: players[0].seat,
+        nonce: new anchor.BN(7),
+        balance: new anchor.BN(230 * Math.pow(10, 9)),
+      };
+      const message = Buffer.concat([
+        Buffer.from("degods-poker:balance"),
+        gameAccount.toBuffer(),
+        Buffer.from([statement.seat]),
+        statement.nonce.toArrayLike(Buffer, "le", 8),
+        statement.balance.toArrayLike(Buffer, "le", 8),
+      ]);
+      const sign = (signer: anchor.web3.Keypair) =>
+        Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: signer.secretKey,
+          message,
+        });
+      // anyone holding the statement can submit it
+      const submitter = anchor.web3.Keypair.generate();
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(submitter.publicKey, 1000000000)
+      );
+      const cashOut = (signers: anchor.web3.Keypair[]) =>
+        program.methods
+          .cashOutSigned(statement)
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            mint,
+            gameAccount,
+            gameTokenAccount: tokenAccount.address,
+            playerTokenAccount: playerTokenAccount.address,
+            player: player.publicKey,
+            payer: submitter.publicKey,
+            pdaAccount: pda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .preInstructions(signers.map(sign))
+          .signers([submitter])
+          .rpc();
+      await expect(cashOut([player])).to.eventually.rejected;
+      await expect(cashOut([payer])).to.eventually.rejected;
+
+      // cover the winnings the statement reports beyond the seat's buy-in
+      await mintTo(
+        connection,
+        payer,
+        mint,
+        tokenAccount.address,
+        payer,
+        30 * Math.pow(10, 9)
+      );
+      await cashOut([player, payer]);
+      const ret = await connection.getTokenAccountBalance(
+        playerTokenAccount.address
+      );
+      expect(ret.value.amount).to.eq("230000000000");
+      const gameState = await program.account.gameAccount.fetch(gameAccount);
+      expect(gameState.players.length).to.eq(0);
+      expect(gameState.statementNonce.toNumber()).to.eq(7);
+    });
+  });
+
+  describe("Roles", () => {
+    it("limits dealers and cashiers to their own instructions", async () => {
+      const { gameAccount, tokenAccount, mint, payer } = await createGame();
+      const { player, playerTokenAccount } = await joinGame({
+        amount: 100 * Math.pow(10, 9),
+        gameAccount,
+        mint,
+        payer,
+        tokenAccount: tokenAccount.address,
+      });
+      const [pda] = PublicKey.findProgramAddressSync(
+        [gameAccount.toBuffer()],
+        MY_PROGRAM_ID
+      );
+      const dealer = anchor.web3.Keypair.generate();
+      const cashier = anchor.web3.Keypair.generate();
+      const dealerRole = findRole(gameAccount, dealer.publicKey);
+      const cashierRole = findRole(gameAccount, cashier.publicKey);
+      await program.methods
+        .grantRole(dealer.publicKey, { dealer: {} })
+        .accounts({
+          roleAccount: dealerRole,
+          scope: gameAccount,
+          owner: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      await program.methods
+        .grantRole(cashier.publicKey, { cashier: {} })
+        .accounts({
+          roleAccount: cashierRole,
+          scope: gameAccount,
+          owner: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      // only the owner hands out roles
+      await expect(
+        program.methods
+          .grantRole(dealer.publicKey, { admin: {} })
+          .accounts({
+            roleAccount: dealerRole,
+            scope: gameAccount,
+            owner: dealer.publicKey,
+          })
+          .signers([dealer])
+          .rpc()
+      ).to.eventually.rejected;
+
+      const shuffleAccount = await commitRound(gameAccount, payer, [player]);
+      await expect(
+        program.methods
+          .startHand()
+          .accounts({
+            shuffleAccount,
+            role: cashierRole,
+            gameAccount,
+            payer: cashier.publicKey,
+          })
+          .signers([cashier])
+          .rpc()
+      ).to.eventually.rejected;
+      await program.methods
+        .startHand()
+        .accounts({
+          role: dealerRole,
+          gameAccount,
+          payer: dealer.publicKey,
+          shuffleAccount,
+        })
+        .signers([dealer])
+        .rpc();
+      await program.methods
+        .settleHand({
+          deltas: [{ player: player.publicKey, delta: new anchor.BN(0) }],
+          rake: new anchor.BN(0),
+          handHash: null,
+        })
+        .accounts({
+          role: dealerRole,
+          gameAccount,
+          payer: dealer.publicKey,
+          program: MY_PROGRAM_ID,
+          eventAuthority,
+        })
+        .signers([dealer])
+        .rpc();
+
+      const refund = (role: PublicKey, signer: Signer) =>
+        program.methods
+          .refundPlayer({ amount: new anchor.BN(10 * Math.pow(10, 9)) })
+          .accounts({
+            eventAuthority,
+            program: MY_PROGRAM_ID,
+            role,
+            mint,
+            gameAccount,
+            gameTokenAccount: tokenAccount.address,
+            playerTokenAccount: playerTokenAccount.address,
+            player: player.publicKey,
+            pdaAccount: pda,
+            payer: signer.publicKey,
+          })
+          .signers([signer])
+          .rpc();
+      await expect(refund(dealerRole, dealer)).to.eventually.rejected;
+      await refund(cashierRole, cashier);
+      const gameState = await program.account.gameAccount.fetch(gameAccount);
+      expect(gameState.players[0].balance.toString()).to.eq("90000000000");
+
+      await program.methods
+        .revokeRole()
+        .accounts({
+          roleAccount: dealerRole,
+          scope: gameAccount,
+          owner: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      expect(await connection.getAccountInfo(dealerRole)).to.eq(null);
     });
   });
 });
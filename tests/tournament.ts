@@ -30,6 +30,12 @@ const program = anchor.workspace
   .DegodsPokerProgram as Program<DegodsPokerProgram>;
 const connection = anchor.getProvider().connection;
 
+// signs the self-CPI that carries emitted events
+const eventAuthority = PublicKey.findProgramAddressSync(
+  [Buffer.from("__event_authority")],
+  MY_PROGRAM_ID
+)[0];
+
 interface CreateTournamentArgs {
   maxPlayers: number;
   entryFee: anchor.BN;
@@ -61,12 +67,34 @@ const mintNFT = async (owner: PublicKey, creator: Keypair) => {
   });
 };
 
+const findHostRegistry = (owner: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("host"), owner.toBuffer()],
+    MY_PROGRAM_ID
+  )[0];
+
+const findRole = (scope: PublicKey, grantee: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("role"), scope.toBuffer(), grantee.toBuffer()],
+    MY_PROGRAM_ID
+  )[0];
+
+const findTournamentAccount = (owner: PublicKey, index: number) =>
+  PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("tournament"),
+      owner.toBuffer(),
+      new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+    ],
+    MY_PROGRAM_ID
+  )[0];
+
 const createTournament = async (
   partial: Partial<CreateTournamentArgs> = {}
 ) => {
   const owner = anchor.web3.Keypair.generate();
   const transactor = anchor.web3.Keypair.generate();
-  const tournamentAccount = anchor.web3.Keypair.generate();
+  const tournamentAccount = findTournamentAccount(owner.publicKey, 0);
   // Specify the rent-exempt reserve to fund the account creation
   const airdropTx = await connection.requestAirdrop(
     owner.publicKey,
@@ -87,7 +115,7 @@ const createTournament = async (
     decimals
   );
   const [pda, bump] = PublicKey.findProgramAddressSync(
-    [tournamentAccount.publicKey.toBuffer()],
+    [tournamentAccount.toBuffer()],
     MY_PROGRAM_ID
   );
   const ownerTokenAccount = await getOrCreateAssociatedTokenAccount(
@@ -126,14 +154,19 @@ const createTournament = async (
       ...partial,
     })
     .accounts({
-      tournamentAccount: tournamentAccount.publicKey,
+      eventAuthority,
+      program: MY_PROGRAM_ID,
+      club: null,
+      mint,
+      hostRegistry: findHostRegistry(owner.publicKey),
+      tournamentAccount,
       owner: owner.publicKey,
       ownerTokenAccount: ownerTokenAccount.address,
       pdaAccount: pda,
       tournamentTokenAccount: tokenAccount.address,
       transactor: transactor.publicKey,
     })
-    .signers([owner, tournamentAccount])
+    .signers([owner])
     .rpc();
 
   return { owner, tournamentAccount, mint, tokenAccount, pda, transactor };
@@ -181,6 +214,10 @@ const registerTournament = async (req: JoinGameArgs) => {
   const tx = await program.methods
     .registerTournament()
     .accounts({
+      eventAuthority,
+      program: MY_PROGRAM_ID,
+      club: null,
+      mint,
       tournamentAccount,
       tournamentTokenAccount: tokenAccount,
       playerTokenAccount: playerTokenAccount.address,
@@ -200,9 +237,7 @@ describe("Tournaments", () => {
       await createTournament({
         guarantee: new anchor.BN(100 * Math.pow(10, 9)),
       });
-    const { data } = await connection.getAccountInfo(
-      tournamentAccount.publicKey
-    );
+    const { data } = await connection.getAccountInfo(tournamentAccount);
     const tournamentState = program.coder.accounts.decode(
       "TournamentAccount",
       data
@@ -226,7 +261,7 @@ describe("Tournaments", () => {
 
     const [tournamentNftPayoutAccount] = PublicKey.findProgramAddressSync(
       [
-        tournamentAccount.publicKey.toBuffer(),
+        tournamentAccount.toBuffer(),
         new anchor.BN(1).toBuffer("le", 2),
       ],
       MY_PROGRAM_ID
@@ -242,10 +277,13 @@ describe("Tournaments", () => {
       .addNftTournamentPrize({ placePaid: 1 })
       .accounts({
         nftTokenAccount: nftTokenAddress,
+        nftMint: mintAddress,
         owner: owner.publicKey,
-        tournamentAccount: tournamentAccount.publicKey,
+        tournamentAccount,
         tournamentNftTokenAccount: tournamentNftAccount.address,
         tournamentNftPayoutAccount,
+        club: null,
+        role: null,
       })
       .signers([owner])
       .rpc();
@@ -263,10 +301,13 @@ describe("Tournaments", () => {
       .addNftTournamentPrize({ placePaid: 1 })
       .accounts({
         nftTokenAccount: nftTokenAddress2,
+        nftMint: mintAddress2,
         owner: owner.publicKey,
-        tournamentAccount: tournamentAccount.publicKey,
+        tournamentAccount,
         tournamentNftTokenAccount: tournamentNftAccount2.address,
         tournamentNftPayoutAccount,
+        club: null,
+        role: null,
       })
       .signers([owner])
       .rpc();
@@ -278,7 +319,7 @@ describe("Tournaments", () => {
     );
     expect(balance.value.uiAmount).to.equal(1);
     expect(ownerBalance.value.uiAmount).to.equal(0);
-    const data = await connection.getAccountInfo(tournamentAccount.publicKey);
+    const data = await connection.getAccountInfo(tournamentAccount);
     const tournamentState = program.coder.accounts.decode(
       "TournamentAccount",
       data.data
@@ -311,17 +352,21 @@ describe("Tournaments", () => {
       500 * Math.pow(10, 9)
     );
     const [tournamentPda] = PublicKey.findProgramAddressSync(
-      [tournamentAccount.publicKey.toBuffer()],
+      [tournamentAccount.toBuffer()],
       MY_PROGRAM_ID
     );
     const [playerPda] = PublicKey.findProgramAddressSync(
-      [tournamentAccount.publicKey.toBuffer(), player.publicKey.toBuffer()],
+      [tournamentAccount.toBuffer(), player.publicKey.toBuffer()],
       MY_PROGRAM_ID
     );
     const tx = await program.methods
       .registerTournament()
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        club: null,
+        mint,
+        tournamentAccount,
         tournamentTokenAccount: tokenAccount.address,
         playerTokenAccount: playerTokenAccount.address,
         player: player.publicKey,
@@ -335,7 +380,11 @@ describe("Tournaments", () => {
       program.methods
         .registerTournament()
         .accounts({
-          tournamentAccount: tournamentAccount.publicKey,
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          club: null,
+          mint,
+          tournamentAccount,
           tournamentTokenAccount: tokenAccount.address,
           playerTokenAccount: playerTokenAccount.address,
           player: player.publicKey,
@@ -357,7 +406,7 @@ describe("Tournaments", () => {
       mint,
       owner,
       tokenAccount: tokenAccount.address,
-      tournamentAccount: tournamentAccount.publicKey,
+      tournamentAccount,
     });
     const balance = await connection.getTokenAccountBalance(
       playerTokenAccount.address
@@ -367,18 +416,19 @@ describe("Tournaments", () => {
     await program.methods
       .unregisterTournament()
       .accounts({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        mint,
         player: player.publicKey,
         pdaAccount: pda,
         playerTokenAccount: playerTokenAccount.address,
-        tournamentAccount: tournamentAccount.publicKey,
+        tournamentAccount,
         tournamentPlayerAccount: playerPda,
         tournamentTokenAccount: tokenAccount.address,
       })
       .signers([player])
       .rpc();
-    const { data } = await connection.getAccountInfo(
-      tournamentAccount.publicKey
-    );
+    const { data } = await connection.getAccountInfo(tournamentAccount);
     const tournamentState = program.coder.accounts.decode(
       "TournamentAccount",
       data
@@ -401,7 +451,7 @@ describe("Tournaments", () => {
       mint,
       owner,
       tokenAccount: tokenAccount.address,
-      tournamentAccount: tournamentAccount.publicKey,
+      tournamentAccount,
     });
     const balance = await connection.getTokenAccountBalance(
       playerTokenAccount.address
@@ -411,9 +461,14 @@ describe("Tournaments", () => {
     await program.methods
       .refundTournament()
       .accounts({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        role: null,
+        club: null,
+        mint,
         pdaAccount: pda,
         playerTokenAccount: playerTokenAccount.address,
-        tournamentAccount: tournamentAccount.publicKey,
+        tournamentAccount,
         tournamentPlayerAccount: playerPda,
         tournamentTokenAccount: tokenAccount.address,
         owner: owner.publicKey,
@@ -421,9 +476,7 @@ describe("Tournaments", () => {
       })
       .signers([owner])
       .rpc();
-    const { data } = await connection.getAccountInfo(
-      tournamentAccount.publicKey
-    );
+    const { data } = await connection.getAccountInfo(tournamentAccount);
     const tournamentState = program.coder.accounts.decode(
       "TournamentAccount",
       data
@@ -443,14 +496,16 @@ describe("Tournaments", () => {
     const tx = await program.methods
       .flipTournamentRegistration()
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        role: null,
+        club: null,
+        tournamentAccount,
         owner: owner.publicKey,
       })
       .signers([owner])
       .rpc();
-    const { data } = await connection.getAccountInfo(
-      tournamentAccount.publicKey
-    );
+    const { data } = await connection.getAccountInfo(tournamentAccount);
     const tournamentState = program.coder.accounts.decode(
       "TournamentAccount",
       data
@@ -468,26 +523,28 @@ describe("Tournaments", () => {
       mint,
       owner,
       tokenAccount: tokenAccount.address,
-      tournamentAccount: tournamentAccount.publicKey,
+      tournamentAccount,
     });
     await registerTournament({
       amount: 500 * Math.pow(10, 9),
       mint,
       owner,
       tokenAccount: tokenAccount.address,
-      tournamentAccount: tournamentAccount.publicKey,
+      tournamentAccount,
     });
     await program.methods
       .startTournament()
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        role: null,
+        club: null,
+        tournamentAccount,
         owner: owner.publicKey,
       })
       .signers([owner])
       .rpc();
-    const { data } = await connection.getAccountInfo(
-      tournamentAccount.publicKey
-    );
+    const { data } = await connection.getAccountInfo(tournamentAccount);
     const tournamentState = program.coder.accounts.decode(
       "TournamentAccount",
       data
@@ -513,7 +570,7 @@ describe("Tournaments", () => {
           mint,
           owner,
           tokenAccount: tokenAccount.address,
-          tournamentAccount: tournamentAccount.publicKey,
+          tournamentAccount,
         });
       players.push(player);
       playerPdas.push(playerPda);
@@ -522,7 +579,11 @@ describe("Tournaments", () => {
     await program.methods
       .startTournament()
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        role: null,
+        club: null,
+        tournamentAccount,
         owner: owner.publicKey,
       })
       .signers([owner])
@@ -532,7 +593,9 @@ describe("Tournaments", () => {
         payouts: [700, 300],
       })
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        role: null,
+        club: null,
+        tournamentAccount,
         owner: owner.publicKey,
       })
       .signers([owner])
@@ -541,11 +604,15 @@ describe("Tournaments", () => {
     await program.methods
       .payoutTournamentPlayer()
       .accounts({
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        club: null,
+        mint,
         owner: owner.publicKey,
         pdaAccount: pda,
         player: players[0].publicKey,
         playerTokenAccount: playerTokenAccounts[0].address,
-        tournamentAccount: tournamentAccount.publicKey,
+        tournamentAccount,
         tournamentPlayerAccount: playerPdas[0],
         tournamentTokenAccount: tokenAccount.address,
       })
@@ -555,7 +622,11 @@ describe("Tournaments", () => {
     await program.methods
       .registerTournament()
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        club: null,
+        mint,
+        tournamentAccount,
         tournamentTokenAccount: tokenAccount.address,
         playerTokenAccount: playerTokenAccounts[0].address,
         player: players[0].publicKey,
@@ -568,7 +639,11 @@ describe("Tournaments", () => {
     await program.methods
       .flipTournamentRegistration()
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        role: null,
+        club: null,
+        tournamentAccount,
         owner: owner.publicKey,
       })
       .signers([owner])
@@ -578,11 +653,15 @@ describe("Tournaments", () => {
       await program.methods
         .payoutTournamentPlayer()
         .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          club: null,
+          mint,
           owner: owner.publicKey,
           pdaAccount: pda,
           player: players[i].publicKey,
           playerTokenAccount: playerTokenAccounts[i].address,
-          tournamentAccount: tournamentAccount.publicKey,
+          tournamentAccount,
           tournamentPlayerAccount: playerPdas[i],
           tournamentTokenAccount: tokenAccount.address,
         })
@@ -606,11 +685,17 @@ describe("Tournaments", () => {
     await program.methods
       .closeTournament()
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        mint,
+        tournamentAccount,
         owner: owner.publicKey,
+        payer: owner.publicKey,
         pdaAccount: pda,
         tournamentTokenAccount: tokenAccount.address,
         ownerTokenAccount: ownerTokenAccount.address,
+        club: null,
+        role: null,
       })
       .signers([owner])
       .rpc();
@@ -641,17 +726,77 @@ describe("Tournaments", () => {
     await program.methods
       .closeTournament()
       .accounts({
-        tournamentAccount: tournamentAccount.publicKey,
+        eventAuthority,
+        program: MY_PROGRAM_ID,
+        mint,
+        tournamentAccount,
         owner: owner.publicKey,
+        payer: owner.publicKey,
         pdaAccount: pda,
         tournamentTokenAccount: tokenAccount.address,
         ownerTokenAccount: ownerTokenAccount.address,
+        club: null,
+        role: null,
+      })
+      .signers([owner])
+      .rpc();
+    const ownerBalance = await connection.getTokenAccountBalance(
+      ownerTokenAccount.address
+    );
+    expect(ownerBalance.value.uiAmount).to.equal(100);
+  });
+
+  it("lets an admin close a tournament, paying the owner", async () => {
+    const { tournamentAccount, owner, tokenAccount, mint, pda } =
+      await createTournament({
+        guarantee: new anchor.BN(100 * Math.pow(10, 9)),
+      });
+    const admin = anchor.web3.Keypair.generate();
+    const airdropTx = await connection.requestAirdrop(
+      admin.publicKey,
+      2000000000
+    );
+    await connection.confirmTransaction(airdropTx);
+    const ownerTokenAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      owner,
+      mint,
+      owner.publicKey,
+      true
+    );
+    const adminRole = findRole(tournamentAccount, admin.publicKey);
+    const closeTournament = (role: PublicKey | null) =>
+      program.methods
+        .closeTournament()
+        .accounts({
+          eventAuthority,
+          program: MY_PROGRAM_ID,
+          mint,
+          tournamentAccount,
+          owner: owner.publicKey,
+          payer: admin.publicKey,
+          pdaAccount: pda,
+          tournamentTokenAccount: tokenAccount.address,
+          ownerTokenAccount: ownerTokenAccount.address,
+          club: null,
+          role,
+        })
+        .signers([admin]);
+    await expect(closeTournament(null).rpc()).to.be.rejected;
+    await program.methods
+      .grantRole(admin.publicKey, { admin: {} })
+      .accounts({
+        roleAccount: adminRole,
+        scope: tournamentAccount,
+        owner: owner.publicKey,
       })
       .signers([owner])
       .rpc();
+    await closeTournament(adminRole).rpc();
     const ownerBalance = await connection.getTokenAccountBalance(
       ownerTokenAccount.address
     );
     expect(ownerBalance.value.uiAmount).to.equal(100);
+    expect(await connection.getAccountInfo(tournamentAccount)).to.eq(null);
   });
 });